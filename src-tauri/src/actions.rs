@@ -0,0 +1,168 @@
+//! Action buttons of ntfy messages (`view`, `http` and `broadcast`), see
+//! https://docs.ntfy.sh/publish/#action-buttons
+
+use anyhow::Result;
+use reqwest::Client;
+use serde::Serialize;
+use std::time::Duration;
+
+use super::ntfy::NtfyAction;
+
+/// ntfy allows at most three actions per message
+pub const MAX_ACTIONS: usize = 3;
+
+/// Event emitted for `broadcast` actions (the desktop stand-in for Android intents)
+pub const BROADCAST_EVENT: &str = "ntfy-broadcast";
+
+/// Actions that can be shown as buttons, in message order
+pub fn buttons(actions: &[NtfyAction]) -> Vec<NtfyAction> {
+    actions
+        .iter()
+        .filter(|a| match a.action.as_str() {
+            "view" | "http" => a.url.as_deref().map_or(false, |url| !url.is_empty()),
+            "broadcast" => true,
+            _ => false,
+        })
+        .filter(|a| !a.label.trim().is_empty())
+        .take(MAX_ACTIONS)
+        .cloned()
+        .collect()
+}
+
+/// Whether a link from a message may be handed to the system opener: only
+/// http(s) URLs, so messages cannot launch `file:`, custom-scheme or other handlers
+pub fn is_openable_url(url: &str) -> bool {
+    reqwest::Url::parse(url.trim()).map_or(false, |url| matches!(url.scheme(), "http" | "https"))
+}
+
+/// Payload of the broadcast event
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct BroadcastPayload {
+    pub topic: String,
+    pub label: String,
+    pub intent: String,
+    pub extras: std::collections::BTreeMap<String, String>,
+}
+
+impl BroadcastPayload {
+    pub fn new(topic: &str, action: &NtfyAction) -> Self {
+        Self {
+            topic: topic.to_string(),
+            label: action.label.clone(),
+            intent: action.intent.clone().unwrap_or_else(|| "io.heckel.ntfy.USER_ACTION".to_string()),
+            extras: action.extras.clone().unwrap_or_default(),
+        }
+    }
+}
+
+/// Send the request of an `http` action. Returns the response status.
+pub async fn send_http(action: &NtfyAction) -> Result<u16> {
+    let url = action.url.as_deref().filter(|u| !u.is_empty()).ok_or_else(|| anyhow::anyhow!("HTTP action without URL"))?;
+    let method = action.method.as_deref().unwrap_or("POST").to_ascii_uppercase();
+    let method = reqwest::Method::from_bytes(method.as_bytes())
+        .map_err(|_| anyhow::anyhow!("Invalid HTTP method '{}'", method))?;
+
+    let client = Client::builder()
+        .timeout(Duration::from_secs(30))
+        .user_agent(format!("ntfy-desktop/{}", env!("CARGO_PKG_VERSION")))
+        .build()?;
+    let mut request = client.request(method, url);
+    for (name, value) in action.headers.iter().flatten() {
+        request = request.header(name.as_str(), value.as_str());
+    }
+    if let Some(body) = &action.body {
+        request = request.body(body.clone());
+    }
+
+    let response = request
+        .send()
+        .await
+        .map_err(|e| anyhow::anyhow!("Action '{}' request failed: {}", action.label, e))?;
+    let status = response.status();
+    if !status.is_success() {
+        return Err(anyhow::anyhow!("Action '{}' returned {}", action.label, status));
+    }
+    Ok(status.as_u16())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    fn action(json: serde_json::Value) -> NtfyAction {
+        serde_json::from_value(json).unwrap()
+    }
+
+    #[test]
+    fn test_buttons() {
+        let actions = vec![
+            action(serde_json::json!({ "action": "view", "label": "Open", "url": "https://example.com" })),
+            action(serde_json::json!({ "action": "view", "label": "No URL" })),
+            action(serde_json::json!({ "action": "unknown", "label": "?" })),
+            action(serde_json::json!({ "action": "broadcast", "label": "Take picture", "extras": { "cmd": "pic" } })),
+            action(serde_json::json!({ "action": "http", "label": "Close door", "url": "https://api.example.com/door" })),
+            action(serde_json::json!({ "action": "http", "label": "Fourth", "url": "https://api.example.com/x" })),
+        ];
+        let labels: Vec<String> = buttons(&actions).into_iter().map(|a| a.label).collect();
+        assert_eq!(labels, vec!["Open", "Take picture", "Close door"]);
+
+        let payload = BroadcastPayload::new("home", &actions[3]);
+        assert_eq!(payload.intent, "io.heckel.ntfy.USER_ACTION");
+        assert_eq!(payload.extras.get("cmd").map(String::as_str), Some("pic"));
+    }
+
+    #[test]
+    fn test_is_openable_url() {
+        assert!(is_openable_url("https://example.com/path?q=1"));
+        assert!(is_openable_url("HTTP://example.com"));
+        assert!(is_openable_url(" https://example.com "));
+
+        assert!(!is_openable_url(""));
+        assert!(!is_openable_url("example.com"));
+        assert!(!is_openable_url("file:///etc/passwd"));
+        assert!(!is_openable_url("javascript:alert(1)"));
+        assert!(!is_openable_url("smb://host/share"));
+        assert!(!is_openable_url("/usr/bin/xterm"));
+        assert!(!is_openable_url("mailto:someone@example.com"));
+    }
+
+    #[tokio::test]
+    async fn test_http_action() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/door", listener.local_addr().unwrap());
+        let server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            let mut buf = [0u8; 4096];
+            while !String::from_utf8_lossy(&request).contains("close-now") {
+                let n = stream.read(&mut buf).await.unwrap();
+                if n == 0 {
+                    break;
+                }
+                request.extend_from_slice(&buf[..n]);
+            }
+            stream.write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\nConnection: close\r\n\r\n").await.unwrap();
+            String::from_utf8_lossy(&request).to_string()
+        });
+
+        let close_door = action(serde_json::json!({
+            "action": "http",
+            "label": "Close door",
+            "url": url,
+            "method": "put",
+            "headers": { "Authorization": "Bearer abc" },
+            "body": "{\"action\": \"close-now\"}",
+        }));
+        assert_eq!(send_http(&close_door).await.unwrap(), 200);
+
+        let request = server.await.unwrap();
+        assert!(request.starts_with("PUT /door"));
+        assert!(request.to_ascii_lowercase().contains("authorization: bearer abc"));
+        assert!(request.ends_with("{\"action\": \"close-now\"}"));
+
+        let invalid = NtfyAction { method: Some("NOT A METHOD".to_string()), ..close_door };
+        assert!(send_http(&invalid).await.is_err());
+    }
+}
@@ -0,0 +1,279 @@
+//! Alarm mode for pages: the notification repeats at an interval, an
+//! always-on-top window asks for acknowledgement and the tray flashes until
+//! then. Open alarms are persisted so a restart doesn't lose a page.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use super::config::NotificationSound;
+use super::notifications::NotificationData;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct AlarmSettings {
+    /// Seconds between repeats of the alarm sound
+    pub repeat_secs: u64,
+    /// Snooze length; the alarm rings again afterwards. 0 disables snoozing.
+    pub snooze_minutes: u64,
+}
+
+impl Default for AlarmSettings {
+    fn default() -> Self {
+        Self { repeat_secs: 30, snooze_minutes: 10 }
+    }
+}
+
+/// A page waiting for acknowledgement
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Alarm {
+    /// Message id
+    pub id: String,
+    pub topic: String,
+    pub title: String,
+    pub message: String,
+    pub priority: u8,
+    /// Message time (unix seconds)
+    pub time: u64,
+    pub sound: NotificationSound,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub snoozed_until: Option<u64>,
+    /// Last time the alarm rang (unix seconds); it rings right away after a restart
+    #[serde(skip)]
+    pub rang_at: u64,
+}
+
+impl Alarm {
+    pub fn is_snoozed(&self, now: u64) -> bool {
+        self.snoozed_until.map_or(false, |until| now < until)
+    }
+
+    /// Notification repeated while the alarm rings; each repeat replaces the previous one
+    pub fn notification(&self) -> NotificationData {
+        NotificationData {
+            title: format!("🚨 {}", self.title),
+            message: self.message.clone(),
+            topic: self.topic.clone(),
+            timestamp: self.time,
+            urgent: true,
+            sound: self.sound.clone(),
+            tag: Some(format!("alarm:{}", self.id)),
+            ..Default::default()
+        }
+    }
+}
+
+/// Open alarms, oldest first
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct AlarmQueue {
+    pub alarms: Vec<Alarm>,
+}
+
+/// What the alarm loop should do at a tick
+#[derive(Debug, Default, PartialEq)]
+pub struct AlarmTick {
+    /// Alarms whose sound is due again
+    pub ring: Vec<Alarm>,
+    /// Some alarm came back from a snooze
+    pub woke: bool,
+    /// Some alarm is ringing (not snoozed)
+    pub active: bool,
+}
+
+impl AlarmQueue {
+    pub fn load(path: &Path) -> Self {
+        match std::fs::read_to_string(path) {
+            Ok(json) => serde_json::from_str(&json).unwrap_or_else(|e| {
+                eprintln!("Ignoring invalid alarm state {}: {}", path.display(), e);
+                Self::default()
+            }),
+            Err(_) => Self::default(),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if self.alarms.is_empty() {
+            if path.exists() {
+                std::fs::remove_file(path)?;
+            }
+            return Ok(());
+        }
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(path, serde_json::to_string(self)?)?;
+        Ok(())
+    }
+
+    /// Add an alarm unless it is already open
+    pub fn raise(&mut self, alarm: Alarm) -> bool {
+        if self.alarms.iter().any(|a| a.id == alarm.id) {
+            return false;
+        }
+        self.alarms.push(alarm);
+        true
+    }
+
+    /// Acknowledge one alarm, or all of them when `id` is `None`
+    pub fn acknowledge(&mut self, id: Option<&str>) -> bool {
+        let before = self.alarms.len();
+        self.alarms.retain(|a| id.map_or(false, |id| a.id != id));
+        self.alarms.len() != before
+    }
+
+    pub fn snooze(&mut self, id: &str, until: u64) -> bool {
+        match self.alarms.iter_mut().find(|a| a.id == id) {
+            Some(alarm) => {
+                alarm.snoozed_until = Some(until);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Advance the alarms to `now`: expired snoozes end and due alarms ring again
+    pub fn tick(&mut self, now: u64, repeat_secs: u64) -> AlarmTick {
+        let mut tick = AlarmTick::default();
+        for alarm in &mut self.alarms {
+            if alarm.is_snoozed(now) {
+                continue;
+            }
+            if alarm.snoozed_until.take().is_some() {
+                tick.woke = true;
+                alarm.rang_at = 0;
+            }
+            tick.active = true;
+            if alarm.rang_at == 0 || now >= alarm.rang_at + repeat_secs.max(1) {
+                alarm.rang_at = now;
+                tick.ring.push(alarm.clone());
+            }
+        }
+        tick
+    }
+}
+
+/// Open alarms shared between the poller, the alarm loop and the alarm window
+pub struct AlarmState {
+    path: Option<PathBuf>,
+    queue: Mutex<AlarmQueue>,
+}
+
+impl AlarmState {
+    pub fn load(path: Option<PathBuf>) -> Self {
+        let queue = path.as_deref().map(AlarmQueue::load).unwrap_or_default();
+        Self { path, queue: Mutex::new(queue) }
+    }
+
+    /// Run `f` on the queue and persist it when `f` reports a change
+    pub fn update<T>(&self, f: impl FnOnce(&mut AlarmQueue) -> T, changed: impl FnOnce(&T) -> bool) -> T {
+        let mut queue = self.queue.lock().unwrap_or_else(|e| e.into_inner());
+        let result = f(&mut queue);
+        if changed(&result) {
+            if let Some(path) = &self.path {
+                if let Err(e) = queue.save(path) {
+                    eprintln!("Failed to save alarm state: {}", e);
+                }
+            }
+        }
+        result
+    }
+
+    pub fn alarms(&self) -> Vec<Alarm> {
+        self.queue.lock().unwrap_or_else(|e| e.into_inner()).alarms.clone()
+    }
+
+    pub fn raise(&self, alarm: Alarm) -> bool {
+        self.update(|queue| queue.raise(alarm), |raised| *raised)
+    }
+
+    pub fn acknowledge(&self, id: Option<&str>) -> bool {
+        self.update(|queue| queue.acknowledge(id), |removed| *removed)
+    }
+
+    pub fn snooze(&self, id: &str, until: u64) -> bool {
+        self.update(|queue| queue.snooze(id, until), |snoozed| *snoozed)
+    }
+
+    pub fn tick(&self, now: u64, repeat_secs: u64) -> AlarmTick {
+        self.update(|queue| queue.tick(now, repeat_secs), |tick| tick.woke)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn alarm(id: &str) -> Alarm {
+        Alarm {
+            id: id.to_string(),
+            topic: "pager".to_string(),
+            title: "Database down".to_string(),
+            message: "primary is unreachable".to_string(),
+            priority: 5,
+            time: 1_700_000_000,
+            sound: NotificationSound::Alert,
+            snoozed_until: None,
+            rang_at: 0,
+        }
+    }
+
+    fn ringing(tick: &AlarmTick) -> Vec<&str> {
+        tick.ring.iter().map(|a| a.id.as_str()).collect()
+    }
+
+    #[test]
+    fn test_alarms_repeat_until_acknowledged() {
+        let mut queue = AlarmQueue::default();
+        assert!(queue.raise(alarm("a")));
+        assert!(!queue.raise(alarm("a")));
+        assert!(queue.raise(alarm("b")));
+
+        assert_eq!(ringing(&queue.tick(100, 30)), vec!["a", "b"]);
+        assert!(queue.tick(110, 30).ring.is_empty());
+        assert_eq!(ringing(&queue.tick(130, 30)), vec!["a", "b"]);
+
+        assert!(queue.acknowledge(Some("a")));
+        assert!(!queue.acknowledge(Some("a")));
+        assert_eq!(ringing(&queue.tick(160, 30)), vec!["b"]);
+        assert!(queue.acknowledge(None));
+        assert!(!queue.tick(200, 30).active);
+    }
+
+    #[test]
+    fn test_snooze_rings_again() {
+        let mut queue = AlarmQueue::default();
+        queue.raise(alarm("a"));
+        queue.tick(100, 30);
+        assert!(queue.snooze("a", 700));
+
+        let tick = queue.tick(400, 30);
+        assert!(!tick.active && tick.ring.is_empty());
+        let tick = queue.tick(700, 30);
+        assert!(tick.woke && tick.active);
+        assert_eq!(ringing(&tick), vec!["a"]);
+        assert!(!queue.tick(710, 30).woke);
+    }
+
+    #[test]
+    fn test_open_alarms_survive_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("alarms.json");
+
+        let state = AlarmState::load(Some(path.clone()));
+        state.raise(alarm("a"));
+        state.raise(alarm("b"));
+        state.acknowledge(Some("a"));
+        state.snooze("b", 500);
+
+        let restored = AlarmState::load(Some(path.clone()));
+        let alarms = restored.alarms();
+        assert_eq!(alarms.len(), 1);
+        assert_eq!(alarms[0].id, "b");
+        assert_eq!(alarms[0].snoozed_until, Some(500));
+
+        restored.acknowledge(None);
+        assert!(!path.exists());
+        assert_eq!(alarm("a").notification().tag.as_deref(), Some("alarm:a"));
+    }
+}
@@ -0,0 +1,163 @@
+//! Unread count badge drawn onto the tray icon (and the Windows taskbar overlay).
+
+use image::{Rgba, RgbaImage};
+
+const BADGE_COLOR: Rgba<u8> = Rgba([220, 53, 69, 255]);
+const TEXT_COLOR: Rgba<u8> = Rgba([255, 255, 255, 255]);
+
+/// Badge height relative to the icon height
+const BADGE_HEIGHT_RATIO: f32 = 0.6;
+
+/// 3x5 bitmap glyphs, one row per byte (bit 2 = left column)
+const DIGITS: [[u8; 5]; 10] = [
+    [0b111, 0b101, 0b101, 0b101, 0b111], // 0
+    [0b010, 0b110, 0b010, 0b010, 0b111], // 1
+    [0b111, 0b001, 0b111, 0b100, 0b111], // 2
+    [0b111, 0b001, 0b111, 0b001, 0b111], // 3
+    [0b101, 0b101, 0b111, 0b001, 0b001], // 4
+    [0b111, 0b100, 0b111, 0b001, 0b111], // 5
+    [0b111, 0b100, 0b111, 0b101, 0b111], // 6
+    [0b111, 0b001, 0b010, 0b010, 0b010], // 7
+    [0b111, 0b101, 0b111, 0b101, 0b111], // 8
+    [0b111, 0b101, 0b111, 0b001, 0b111], // 9
+];
+const PLUS: [u8; 5] = [0b000, 0b010, 0b111, 0b010, 0b000];
+
+/// Text shown in the badge, `None` when there is nothing unread
+pub fn label(count: u32) -> Option<String> {
+    match count {
+        0 => None,
+        1..=99 => Some(count.to_string()),
+        _ => Some("99+".to_string()),
+    }
+}
+
+fn glyph(c: char) -> [u8; 5] {
+    match c.to_digit(10) {
+        Some(d) => DIGITS[d as usize],
+        None => PLUS,
+    }
+}
+
+/// Fill a pill (rounded rectangle with fully round ends) and center the label in it
+fn draw_pill(img: &mut RgbaImage, x0: u32, y0: u32, width: u32, height: u32, text: &str) {
+    let radius = height as f32 / 2.0;
+    let center_y = y0 as f32 + radius;
+    let left = x0 as f32 + radius;
+    let right = (x0 + width) as f32 - radius;
+
+    for y in y0..(y0 + height).min(img.height()) {
+        for x in x0..(x0 + width).min(img.width()) {
+            let px = x as f32 + 0.5;
+            let py = y as f32 + 0.5;
+            let dx = if px < left { left - px } else if px > right { px - right } else { 0.0 };
+            let dy = py - center_y;
+            if dx * dx + dy * dy <= radius * radius {
+                img.put_pixel(x, y, BADGE_COLOR);
+            }
+        }
+    }
+
+    let glyph_count = text.chars().count() as u32;
+    let scale = glyph_scale(height);
+    let text_width = glyph_count * 4 * scale - scale;
+    let text_x = x0 + width.saturating_sub(text_width) / 2;
+    let text_y = y0 + height.saturating_sub(5 * scale) / 2;
+
+    for (i, c) in text.chars().enumerate() {
+        let gx = text_x + i as u32 * 4 * scale;
+        for (row, bits) in glyph(c).iter().enumerate() {
+            for col in 0..3u32 {
+                if bits & (0b100 >> col) == 0 {
+                    continue;
+                }
+                for sy in 0..scale {
+                    for sx in 0..scale {
+                        let x = gx + col * scale + sx;
+                        let y = text_y + row as u32 * scale + sy;
+                        if x < img.width() && y < img.height() {
+                            img.put_pixel(x, y, TEXT_COLOR);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Pixel size of one glyph cell for a badge of the given height (glyphs fill ~60% of it)
+fn glyph_scale(height: u32) -> u32 {
+    (height * 3 / 25).max(1)
+}
+
+/// Width of a badge of the given height that fits the label
+fn pill_width(height: u32, text: &str) -> u32 {
+    let scale = glyph_scale(height);
+    let text_width = text.chars().count() as u32 * 4 * scale - scale;
+    (text_width + 4 * scale).max(height)
+}
+
+/// Draw the unread badge in the top-right corner of an icon
+pub fn draw_badge(icon: &mut RgbaImage, count: u32) {
+    let Some(text) = label(count) else { return };
+
+    let height = ((icon.height() as f32 * BADGE_HEIGHT_RATIO) as u32).max(8).min(icon.height());
+    let width = pill_width(height, &text).min(icon.width());
+    draw_pill(icon, icon.width() - width, 0, width, height, &text);
+}
+
+/// Standalone badge image (for the Windows taskbar overlay icon)
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+pub fn overlay_icon(count: u32, size: u32) -> Option<RgbaImage> {
+    let text = label(count)?;
+    let mut img = RgbaImage::new(size, size);
+    let width = pill_width(size, &text).min(size);
+    draw_pill(&mut img, (size - width) / 2, 0, width, size, &text);
+    Some(img)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TRANSPARENT: Rgba<u8> = Rgba([0, 0, 0, 0]);
+
+    #[test]
+    fn test_label() {
+        assert_eq!(label(0), None);
+        assert_eq!(label(7).as_deref(), Some("7"));
+        assert_eq!(label(42).as_deref(), Some("42"));
+        assert_eq!(label(100).as_deref(), Some("99+"));
+    }
+
+    #[test]
+    fn test_draw_badge_top_right() {
+        let mut icon = RgbaImage::new(32, 32);
+        draw_badge(&mut icon, 3);
+
+        // Badge is 19px tall in the top-right corner, the rest of the icon is untouched
+        assert_eq!(*icon.get_pixel(31, 9), BADGE_COLOR);
+        assert_eq!(*icon.get_pixel(0, 31), TRANSPARENT);
+        assert_eq!(*icon.get_pixel(31, 25), TRANSPARENT);
+        assert!(icon.pixels().any(|p| *p == TEXT_COLOR));
+    }
+
+    #[test]
+    fn test_no_badge_without_unread() {
+        let mut icon = RgbaImage::from_pixel(16, 16, Rgba([1, 2, 3, 255]));
+        draw_badge(&mut icon, 0);
+        assert!(icon.pixels().all(|p| *p == Rgba([1, 2, 3, 255])));
+        assert!(overlay_icon(0, 16).is_none());
+    }
+
+    #[test]
+    fn test_wide_label_fits() {
+        let mut icon = RgbaImage::new(16, 16);
+        draw_badge(&mut icon, 250);
+        assert!(icon.pixels().any(|p| *p == TEXT_COLOR));
+
+        let overlay = overlay_icon(250, 16).unwrap();
+        assert_eq!(overlay.dimensions(), (16, 16));
+        assert_eq!(*overlay.get_pixel(8, 15), BADGE_COLOR);
+    }
+}
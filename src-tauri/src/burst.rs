@@ -0,0 +1,137 @@
+//! Burst coalescing: once a topic sends more than `threshold` messages within
+//! `window_secs`, further toasts are folded into one summary per topic that is
+//! replaced as messages keep arriving. Urgent messages are never folded.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+
+use super::notifications::NotificationData;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct BurstSettings {
+    pub enabled: bool,
+    /// Messages per topic shown individually within the window
+    pub threshold: u32,
+    pub window_secs: u64,
+}
+
+impl Default for BurstSettings {
+    fn default() -> Self {
+        Self { enabled: true, threshold: 5, window_secs: 60 }
+    }
+}
+
+/// How the notification step treats a message
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BurstDecision {
+    Show,
+    /// Fold into the topic's summary; `count` messages were folded so far
+    Summarize { count: u32 },
+}
+
+#[derive(Debug, Default)]
+struct TopicBurst {
+    /// Arrival times (unix seconds) within the window
+    arrivals: VecDeque<u64>,
+    summarized: u32,
+}
+
+/// Per-topic arrival counts, kept for the lifetime of the poller
+#[derive(Debug, Default)]
+pub struct BurstTracker {
+    topics: HashMap<String, TopicBurst>,
+}
+
+impl BurstTracker {
+    /// Record a message that is about to be shown and decide whether it gets its own toast
+    pub fn record(&mut self, settings: &BurstSettings, topic: &str, urgent: bool, now: u64) -> BurstDecision {
+        if !settings.enabled || urgent {
+            return BurstDecision::Show;
+        }
+        let burst = self.topics.entry(topic.to_string()).or_default();
+        while burst.arrivals.front().map_or(false, |&t| t + settings.window_secs <= now) {
+            burst.arrivals.pop_front();
+        }
+        if burst.arrivals.len() < settings.threshold as usize {
+            // The burst is over: the next one starts a new summary
+            burst.summarized = 0;
+        }
+        burst.arrivals.push_back(now);
+        if burst.arrivals.len() <= settings.threshold as usize {
+            return BurstDecision::Show;
+        }
+        burst.summarized += 1;
+        BurstDecision::Summarize { count: burst.summarized }
+    }
+}
+
+/// Summary toast for a topic, showing the latest message. It carries no click
+/// URL or actions, so clicking it opens the topic where the rest can be read.
+pub fn summary(latest: &NotificationData, count: u32, topic_label: &str) -> NotificationData {
+    NotificationData {
+        title: format!("{} new in {}", count, topic_label),
+        message: format!("Latest: {}\n{}", latest.title, latest.message),
+        tag: Some(format!("burst:{}", latest.topic)),
+        click: None,
+        actions: Vec::new(),
+        persistent: false,
+        ..latest.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings() -> BurstSettings {
+        BurstSettings { enabled: true, threshold: 3, window_secs: 60 }
+    }
+
+    #[test]
+    fn test_burst_is_summarized_after_threshold() {
+        let mut tracker = BurstTracker::default();
+        let decisions: Vec<BurstDecision> = (0..6).map(|i| tracker.record(&settings(), "ci", false, 1000 + i)).collect();
+        assert_eq!(
+            decisions,
+            vec![
+                BurstDecision::Show,
+                BurstDecision::Show,
+                BurstDecision::Show,
+                BurstDecision::Summarize { count: 1 },
+                BurstDecision::Summarize { count: 2 },
+                BurstDecision::Summarize { count: 3 },
+            ]
+        );
+
+        // Other topics and urgent messages are unaffected
+        assert_eq!(tracker.record(&settings(), "backups", false, 1006), BurstDecision::Show);
+        assert_eq!(tracker.record(&settings(), "ci", true, 1006), BurstDecision::Show);
+        assert_eq!(tracker.record(&settings(), "ci", false, 1007), BurstDecision::Summarize { count: 4 });
+
+        // Once the window has passed, messages are shown individually again
+        assert_eq!(tracker.record(&settings(), "ci", false, 1200), BurstDecision::Show);
+        let disabled = BurstSettings { enabled: false, ..settings() };
+        for i in 0..10 {
+            assert_eq!(tracker.record(&disabled, "ci", false, 1300 + i), BurstDecision::Show);
+        }
+    }
+
+    #[test]
+    fn test_summary_notification() {
+        let latest = NotificationData {
+            title: "Build failed".to_string(),
+            message: "main is red\n\nTopic: CI".to_string(),
+            topic: "ci-alerts".to_string(),
+            click: Some("https://ci.example.com/42".to_string()),
+            persistent: true,
+            ..Default::default()
+        };
+        let data = summary(&latest, 14, "ci-alerts");
+        assert_eq!(data.title, "14 new in ci-alerts");
+        assert_eq!(data.message, "Latest: Build failed\nmain is red\n\nTopic: CI");
+        assert_eq!(data.tag.as_deref(), Some("burst:ci-alerts"));
+        assert_eq!(data.click, None);
+        assert!(!data.persistent);
+    }
+}
@@ -2,6 +2,17 @@ use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use tauri::{AppHandle, Manager};
 
+use super::alarm::AlarmSettings;
+use super::burst::BurstSettings;
+use super::email::EmailSettings;
+use super::hooks::HookSettings;
+use super::priority::PriorityPolicies;
+use super::quiet_hours::QuietHours;
+use super::rate_limit::RateLimits;
+use super::rules::{Rule, RuleAction};
+use super::subscriptions::{self, TopicSettings, TopicSubscription};
+use super::webhook::WebhookSink;
+
 /// Persistent notification mode
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "snake_case")]
@@ -27,6 +38,8 @@ pub enum NotificationSound {
     Bell,
     Chime,
     Pop,
+    /// WAV, OGG or FLAC file played by the app
+    Custom(String),
 }
 
 impl Default for NotificationSound {
@@ -35,6 +48,15 @@ impl Default for NotificationSound {
     }
 }
 
+/// Active notification pause: messages are still received and stored, toasts are suppressed
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PauseState {
+    /// When the pause started (unix seconds)
+    pub since: u64,
+    /// When notifications resume (unix seconds)
+    pub until: u64,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(default)]
 pub struct AppConfig {
@@ -45,7 +67,9 @@ pub struct AppConfig {
     pub auth_user: String,
     #[serde(default)]
     pub auth_pass: String,
-    pub topics: String,
+    /// Subscribed topics; a comma-separated string (older configs) is migrated on load
+    #[serde(deserialize_with = "subscriptions::deserialize_topics")]
+    pub topics: Vec<TopicSubscription>,
     pub poll_rate: u64,
     pub datetime_format: String,
     // Deprecated: kept for migration, use persistent_notifications_mode instead
@@ -60,7 +84,48 @@ pub struct AppConfig {
     pub hotkeys_enabled: bool,
     pub dev_tools: bool,
     pub welcome_completed: bool,
+    // Deprecated: kept for migration, use priority_policies instead
     pub urgent_priority_threshold: u8,
+    /// What each priority level does. Configs from before per-priority policies
+    /// lack it; `migrate` derives it from the sounds, persistence and threshold above.
+    #[serde(default)]
+    pub priority_policies: Option<PriorityPolicies>,
+    /// Repeat and snooze for priorities in alarm mode
+    pub alarm: AlarmSettings,
+    /// Topics whose messages are end-to-end encrypted (keys live in the OS keychain)
+    #[serde(default)]
+    pub encrypted_topics: Vec<String>,
+    /// Act as a UnifiedPush distributor over D-Bus (Linux only, applied on restart)
+    #[serde(default)]
+    pub unifiedpush_enabled: bool,
+    /// Keep received messages in the local history database
+    pub history_enabled: bool,
+    /// Maximum number of stored messages (0 = unlimited)
+    pub history_max_messages: u32,
+    /// Delete stored messages older than this many days (0 = keep forever)
+    pub history_retention_days: u32,
+    // Deprecated: kept for migration, mutes now live on each subscription
+    #[serde(default, skip_serializing)]
+    pub muted_topics: Vec<String>,
+    /// Current pause, kept across restarts
+    #[serde(default)]
+    pub pause: Option<PauseState>,
+    /// Priority 5 messages still show while paused
+    pub pause_allow_urgent: bool,
+    /// Scheduled do-not-disturb for low-priority messages
+    pub quiet_hours: QuietHours,
+    /// Bursts of messages on one topic become a single summary toast
+    pub burst: BurstSettings,
+    /// Toasts per minute for each topic and overall
+    pub rate_limits: RateLimits,
+    /// Rules applied to incoming messages, in order
+    pub rules: Vec<Rule>,
+    /// Limits for commands run by topic and rule hooks (applied on restart)
+    pub hooks: HookSettings,
+    /// URLs that incoming messages are POSTed to
+    pub webhooks: Vec<WebhookSink>,
+    /// Messages forwarded by email
+    pub email: EmailSettings,
 }
 
 impl Default for AppConfig {
@@ -70,7 +135,7 @@ impl Default for AppConfig {
             api_token: String::new(),
             auth_user: String::new(),
             auth_pass: String::new(),
-            topics: "announcements,stats".to_string(),
+            topics: subscriptions::parse_topics("announcements,stats"),
             poll_rate: 60,
             datetime_format: "YYYY-MM-DD hh:mm a".to_string(),
             persistent_notifications: false,
@@ -84,6 +149,23 @@ impl Default for AppConfig {
             dev_tools: false,
             welcome_completed: false,
             urgent_priority_threshold: 4,
+            priority_policies: Some(PriorityPolicies::default()),
+            alarm: AlarmSettings::default(),
+            encrypted_topics: Vec::new(),
+            unifiedpush_enabled: false,
+            history_enabled: true,
+            history_max_messages: 10_000,
+            history_retention_days: 90,
+            muted_topics: Vec::new(),
+            pause: None,
+            pause_allow_urgent: true,
+            quiet_hours: QuietHours::default(),
+            burst: BurstSettings::default(),
+            rate_limits: RateLimits::default(),
+            rules: Vec::new(),
+            hooks: HookSettings::default(),
+            webhooks: Vec::new(),
+            email: EmailSettings::default(),
         }
     }
 }
@@ -100,12 +182,77 @@ impl AppConfig {
     /// Get the topics as a vector
     pub fn topics_list(&self) -> Vec<String> {
         self.topics
-            .split(',')
-            .map(|s| s.trim().to_string())
+            .iter()
+            .map(|s| s.topic.trim().to_string())
             .filter(|s| !s.is_empty())
             .collect()
     }
 
+    /// Subscription for a topic, if subscribed
+    pub fn subscription(&self, topic: &str) -> Option<&TopicSubscription> {
+        self.topics.iter().find(|s| s.topic == topic)
+    }
+
+    /// Effective settings for a topic: subscription overrides on top of the global values
+    pub fn topic_settings(&self, topic: &str) -> TopicSettings {
+        let sub = self.subscription(topic);
+        TopicSettings {
+            display_name: sub.map_or(topic, |s| s.label()).to_string(),
+            muted: sub.map_or(false, |s| s.is_muted_at(now_secs())),
+            policies: sub
+                .and_then(|s| self.subscription_policies(s))
+                .unwrap_or_else(|| self.policies()),
+            icon: sub.and_then(|s| s.icon.clone()).filter(|i| !i.is_empty()),
+            hook: sub.and_then(|s| s.hook.clone()).filter(|h| !h.command.trim().is_empty()),
+            journal: sub.map_or(false, |s| s.journal),
+            trusted: sub.map_or(false, |s| s.trusted),
+        }
+    }
+
+    /// Move settings from older config formats into their current place
+    pub fn migrate(&mut self) {
+        for topic in std::mem::take(&mut self.muted_topics) {
+            if let Some(sub) = self.topics.iter_mut().find(|s| s.topic == topic) {
+                sub.muted = true;
+            }
+        }
+        for i in 0..self.topics.len() {
+            if let Some(policies) = self.subscription_policies(&self.topics[i]) {
+                self.topics[i].priority_policies = Some(policies);
+            }
+            self.topics[i].clear_legacy_overrides();
+        }
+        if self.priority_policies.is_none() {
+            self.priority_policies = Some(self.policies());
+        }
+    }
+
+    /// A topic's own policies, derived from its old sound, persistence and threshold
+    /// overrides (on top of the global values) when not configured
+    fn subscription_policies(&self, sub: &TopicSubscription) -> Option<PriorityPolicies> {
+        if sub.priority_policies.is_some() || !sub.has_legacy_overrides() {
+            return sub.priority_policies.clone();
+        }
+        Some(PriorityPolicies::from_threshold(
+            sub.urgent_priority_threshold.unwrap_or(self.urgent_priority_threshold),
+            sub.sound.as_ref().unwrap_or(&self.notification_sound),
+            sub.urgent_sound.as_ref().unwrap_or(&self.urgent_notification_sound),
+            sub.persistence.as_ref().unwrap_or(&self.persistent_notifications_mode),
+        ))
+    }
+
+    /// Per-priority policies, derived from the urgency threshold when not configured
+    pub fn policies(&self) -> PriorityPolicies {
+        self.priority_policies.clone().unwrap_or_else(|| {
+            PriorityPolicies::from_threshold(
+                self.urgent_priority_threshold,
+                &self.notification_sound,
+                &self.urgent_notification_sound,
+                &self.persistent_notifications_mode,
+            )
+        })
+    }
+
     /// Get the topics as a comma-separated path for the ntfy API
     pub fn topics_path(&self) -> String {
         self.topics_list().join(",")
@@ -125,6 +272,65 @@ impl AppConfig {
         }
     }
 
+    /// Check if a topic is configured for end-to-end encryption
+    pub fn is_topic_encrypted(&self, topic: &str) -> bool {
+        self.encrypted_topics.iter().any(|t| t == topic)
+    }
+
+    /// Check if toasts for a topic are muted
+    pub fn is_topic_muted(&self, topic: &str) -> bool {
+        self.subscription(topic).map_or(false, |s| s.is_muted_at(now_secs()))
+    }
+
+    /// Mute (until unmuted) or unmute a subscribed topic
+    pub fn set_topic_muted(&mut self, topic: &str, muted: bool) {
+        if let Some(sub) = self.topics.iter_mut().find(|s| s.topic == topic) {
+            sub.muted = muted;
+            sub.muted_until = None;
+        }
+    }
+
+    /// Mute a subscribed topic until `until` (unix seconds). Returns false, changing
+    /// nothing, for topics without a subscription entry: adding one would subscribe to it.
+    pub fn mute_topic_until(&mut self, topic: &str, until: u64) -> bool {
+        match self.topics.iter_mut().find(|s| s.topic == topic) {
+            Some(sub) => {
+                sub.muted = true;
+                sub.muted_until = Some(until);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Shell commands the config runs: topic hooks, then rule `RunCommand` actions
+    pub fn commands(&self) -> Vec<&str> {
+        let hooks = self.topics.iter().filter_map(|s| s.hook.as_ref()).map(|h| h.command.as_str());
+        let rules = self.rules.iter().flat_map(|r| &r.actions).filter_map(|a| match a {
+            RuleAction::RunCommand { command, .. } => Some(command.as_str()),
+            _ => None,
+        });
+        hooks.chain(rules).collect()
+    }
+
+    /// Paths of the custom sound files used anywhere in the config
+    pub fn custom_sounds(&self) -> Vec<&str> {
+        let policies = self.priority_policies.iter().flat_map(|p| p.iter()).map(|p| &p.sound);
+        let topics = self
+            .topics
+            .iter()
+            .flat_map(|s| s.priority_policies.iter().flat_map(|p| p.iter()).map(|p| &p.sound));
+        [&self.notification_sound, &self.urgent_notification_sound]
+            .into_iter()
+            .chain(policies)
+            .chain(topics)
+            .filter_map(|sound| match sound {
+                NotificationSound::Custom(path) => Some(path.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
     /// Get the sound to use for a notification based on urgency
     pub fn notification_sound_for(&self, is_urgent: bool) -> &NotificationSound {
         if is_urgent {
@@ -135,6 +341,10 @@ impl AppConfig {
     }
 }
 
+fn now_secs() -> u64 {
+    chrono::Utc::now().timestamp().max(0) as u64
+}
+
 pub async fn save_config(app_handle: &AppHandle, config: AppConfig) -> Result<()> {
     let app_dir = app_handle
         .path()
@@ -182,8 +392,9 @@ pub async fn load_config(app_handle: &AppHandle) -> Result<AppConfig> {
     let config_str = String::from_utf8(config_data)
         .map_err(|e| anyhow::anyhow!("Config file {} contains invalid UTF-8: {}", config_path.display(), e))?;
 
-    let config: AppConfig = serde_json::from_str(&config_str)
+    let mut config: AppConfig = serde_json::from_str(&config_str)
         .map_err(|e| anyhow::anyhow!("Failed to parse config file {}: {}", config_path.display(), e))?;
+    config.migrate();
 
     println!("Config loaded successfully from: {}", config_path.display());
     Ok(config)
@@ -203,7 +414,7 @@ mod tests {
         assert_eq!(config.api_token, "");
         assert_eq!(config.auth_user, "");
         assert_eq!(config.auth_pass, "");
-        assert_eq!(config.topics, "announcements,stats");
+        assert_eq!(config.topics_list(), vec!["announcements", "stats"]);
         assert_eq!(config.poll_rate, 60);
         assert_eq!(config.datetime_format, "YYYY-MM-DD hh:mm a");
         assert!(!config.persistent_notifications);
@@ -217,6 +428,29 @@ mod tests {
         assert!(!config.dev_tools);
         assert!(!config.welcome_completed);
         assert_eq!(config.urgent_priority_threshold, 4);
+        assert_eq!(config.priority_policies, Some(PriorityPolicies::default()));
+        assert_eq!(config.alarm.repeat_secs, 30);
+        assert_eq!(config.alarm.snooze_minutes, 10);
+        assert!(config.encrypted_topics.is_empty());
+        assert!(!config.unifiedpush_enabled);
+        assert!(config.history_enabled);
+        assert_eq!(config.history_max_messages, 10_000);
+        assert_eq!(config.history_retention_days, 90);
+        assert!(config.muted_topics.is_empty());
+        assert_eq!(config.pause, None);
+        assert!(config.pause_allow_urgent);
+        assert!(!config.quiet_hours.enabled);
+        assert!(config.burst.enabled);
+        assert_eq!(config.burst.threshold, 5);
+        assert_eq!(config.burst.window_secs, 60);
+        assert!(config.rate_limits.enabled);
+        assert_eq!(config.rate_limits.mute_minutes, 10);
+        assert!(config.rules.is_empty());
+        assert_eq!(config.hooks.max_concurrent, 4);
+        assert_eq!(config.hooks.timeout_secs, 30);
+        assert!(config.webhooks.is_empty());
+        assert!(!config.email.enabled);
+        assert_eq!(config.email.port, 587);
     }
 
     #[test]
@@ -226,13 +460,17 @@ mod tests {
             api_token: "test-token".to_string(),
             auth_user: "testuser".to_string(),
             auth_pass: "testpass".to_string(),
-            topics: "topic1,topic2,topic3".to_string(),
+            topics: vec![
+                TopicSubscription::new("topic1"),
+                TopicSubscription::new("topic2"),
+                TopicSubscription { muted: true, ..TopicSubscription::new("topic3") },
+            ],
             poll_rate: 30,
             datetime_format: "MM/DD/YYYY HH:mm".to_string(),
             persistent_notifications: true,
             persistent_notifications_mode: PersistentNotificationMode::UrgentOnly,
             notification_sound: NotificationSound::Chime,
-            urgent_notification_sound: NotificationSound::Alert,
+            urgent_notification_sound: NotificationSound::Custom("/sounds/siren.ogg".to_string()),
             self_hosted_instance: true,
             start_hidden: true,
             quit_on_close: true,
@@ -240,6 +478,42 @@ mod tests {
             dev_tools: true,
             welcome_completed: true,
             urgent_priority_threshold: 4,
+            priority_policies: Some(PriorityPolicies {
+                low: crate::priority::PriorityPolicy { popup: false, ..Default::default() },
+                ..Default::default()
+            }),
+            alarm: AlarmSettings { repeat_secs: 60, snooze_minutes: 0 },
+            encrypted_topics: vec!["topic2".to_string()],
+            unifiedpush_enabled: true,
+            history_enabled: false,
+            history_max_messages: 500,
+            history_retention_days: 7,
+            muted_topics: Vec::new(),
+            pause: Some(PauseState { since: 1_700_000_000, until: 1_700_001_800 }),
+            pause_allow_urgent: false,
+            quiet_hours: QuietHours { enabled: true, timezone: "Europe/Berlin".to_string(), ..Default::default() },
+            burst: BurstSettings { enabled: false, threshold: 10, window_secs: 300 },
+            rate_limits: RateLimits { topic_per_minute: 5, mute_minutes: 30, ..Default::default() },
+            rules: vec![Rule {
+                name: "mute heartbeats".to_string(),
+                enabled: true,
+                conditions: Default::default(),
+                actions: vec![crate::rules::RuleAction::Suppress],
+            }],
+            hooks: HookSettings { max_concurrent: 2, timeout_secs: 10 },
+            webhooks: vec![WebhookSink {
+                name: "dashboard".to_string(),
+                url: "https://dashboard.example.com/alerts".to_string(),
+                topics: vec!["topic1".to_string()],
+                ..Default::default()
+            }],
+            email: EmailSettings {
+                enabled: true,
+                server: "smtp.example.com".to_string(),
+                from: "ntfy@example.com".to_string(),
+                digest_minutes: 15,
+                ..Default::default()
+            },
         };
 
         let serialized = serde_json::to_string(&config)
@@ -283,6 +557,77 @@ mod tests {
         assert_eq!(config.hotkeys_enabled, deserialized.hotkeys_enabled);
         assert_eq!(config.dev_tools, deserialized.dev_tools);
         assert_eq!(config.welcome_completed, deserialized.welcome_completed);
+        assert_eq!(config.priority_policies, deserialized.priority_policies);
+        assert_eq!(config.alarm, deserialized.alarm);
+        assert_eq!(deserialized.custom_sounds(), vec!["/sounds/siren.ogg"]);
+        assert_eq!(
+            serde_json::to_value(&config.urgent_notification_sound).unwrap(),
+            serde_json::json!({ "custom": "/sounds/siren.ogg" })
+        );
+        assert_eq!(config.encrypted_topics, deserialized.encrypted_topics);
+        assert!(deserialized.is_topic_encrypted("topic2"));
+        assert!(!deserialized.is_topic_encrypted("topic1"));
+        assert_eq!(config.unifiedpush_enabled, deserialized.unifiedpush_enabled);
+        assert_eq!(config.history_enabled, deserialized.history_enabled);
+        assert_eq!(config.history_max_messages, deserialized.history_max_messages);
+        assert_eq!(config.history_retention_days, deserialized.history_retention_days);
+        assert!(deserialized.is_topic_muted("topic3"));
+        assert!(!deserialized.is_topic_muted("topic1"));
+        assert_eq!(config.pause, deserialized.pause);
+        assert_eq!(config.pause_allow_urgent, deserialized.pause_allow_urgent);
+        assert_eq!(config.quiet_hours, deserialized.quiet_hours);
+        assert_eq!(config.burst, deserialized.burst);
+        assert_eq!(config.rate_limits, deserialized.rate_limits);
+        assert_eq!(config.rules, deserialized.rules);
+        assert_eq!(config.hooks, deserialized.hooks);
+        assert_eq!(config.webhooks, deserialized.webhooks);
+        assert_eq!(config.email, deserialized.email);
+    }
+
+    #[test]
+    fn test_commands() {
+        let mut config = AppConfig {
+            topics: vec![
+                TopicSubscription {
+                    hook: Some(crate::hooks::HookCommand { command: "notify-light".to_string(), timeout_secs: None }),
+                    ..TopicSubscription::new("alerts")
+                },
+                TopicSubscription::new("plain"),
+            ],
+            ..Default::default()
+        };
+        assert_eq!(config.commands(), vec!["notify-light"]);
+
+        config.rules = vec![Rule {
+            name: "page".to_string(),
+            enabled: true,
+            conditions: Default::default(),
+            actions: vec![
+                RuleAction::Persist,
+                RuleAction::RunCommand { command: "page-oncall".to_string(), timeout_secs: Some(5) },
+            ],
+        }];
+        assert_eq!(config.commands(), vec!["notify-light", "page-oncall"]);
+    }
+
+    #[test]
+    fn test_mute_topic_until() {
+        let mut config = AppConfig {
+            topics: vec![TopicSubscription::new("topic1")],
+            ..Default::default()
+        };
+
+        assert!(config.mute_topic_until("topic1", u64::MAX));
+        assert!(config.is_topic_muted("topic1"));
+
+        // An expired mute no longer applies
+        assert!(config.mute_topic_until("topic1", 1));
+        assert!(!config.is_topic_muted("topic1"));
+
+        // Unsubscribed topics are left alone rather than subscribed to
+        assert!(!config.mute_topic_until("other", u64::MAX));
+        assert!(!config.is_topic_muted("other"));
+        assert_eq!(config.topics_list(), vec!["topic1"]);
     }
 
     #[test]
@@ -319,22 +664,22 @@ mod tests {
         let mut config = AppConfig::default();
 
         // Test normal comma-separated topics
-        config.topics = "topic1,topic2,topic3".to_string();
+        config.topics = subscriptions::parse_topics("topic1,topic2,topic3");
         assert_eq!(config.topics_list(), vec!["topic1", "topic2", "topic3"]);
         assert_eq!(config.topics_path(), "topic1,topic2,topic3");
 
         // Test with spaces
-        config.topics = "topic1, topic2, topic3".to_string();
+        config.topics = subscriptions::parse_topics("topic1, topic2, topic3");
         assert_eq!(config.topics_list(), vec!["topic1", "topic2", "topic3"]);
         assert_eq!(config.topics_path(), "topic1,topic2,topic3");
 
         // Test with empty topics
-        config.topics = "".to_string();
+        config.topics = subscriptions::parse_topics("");
         assert_eq!(config.topics_list(), Vec::<String>::new());
         assert_eq!(config.topics_path(), "");
 
         // Test with extra commas
-        config.topics = "topic1,,topic2,".to_string();
+        config.topics = subscriptions::parse_topics("topic1,,topic2,");
         assert_eq!(config.topics_list(), vec!["topic1", "topic2"]);
         assert_eq!(config.topics_path(), "topic1,topic2");
     }
@@ -372,7 +717,7 @@ mod tests {
         // Test saving config (credentials stored in keychain, not on disk)
         let config = AppConfig {
             instance_url: "https://test.example.com".to_string(),
-            topics: "test-topic".to_string(),
+            topics: subscriptions::parse_topics("test-topic"),
             poll_rate: 120,
             datetime_format: "YYYY-MM-DD HH:mm".to_string(),
             persistent_notifications: true,
@@ -463,7 +808,7 @@ mod tests {
         // Test with empty strings
         let empty_config = AppConfig {
             instance_url: "".to_string(),
-            topics: "".to_string(),
+            topics: subscriptions::parse_topics(""),
             poll_rate: 0,
             datetime_format: "".to_string(),
             ..Default::default()
@@ -484,7 +829,7 @@ mod tests {
         // Test with special characters
         let special_config = AppConfig {
             instance_url: "https://example.com/path?query=test&param=value".to_string(),
-            topics: "topic-with-ünicode,another-topic".to_string(),
+            topics: subscriptions::parse_topics("topic-with-ünicode,another-topic"),
             poll_rate: 123,
             datetime_format: "YYYY年MM月DD日 HH時mm分".to_string(),
             persistent_notifications: true,
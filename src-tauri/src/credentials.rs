@@ -82,3 +82,65 @@ pub fn save_credentials(creds: &Credentials) -> Result<()> {
 
     Ok(())
 }
+
+/// Keychain key holding the derived encryption key for a topic
+fn topic_key_name(topic: &str) -> String {
+    format!("topic_key:{}", topic)
+}
+
+/// Load the encoded encryption key for a topic (empty if none is stored)
+pub fn load_topic_key(topic: &str) -> Result<String> {
+    get_secret(&topic_key_name(topic))
+}
+
+/// Save the encoded encryption key for a topic (an empty value deletes it)
+pub fn save_topic_key(topic: &str, encoded_key: &str) -> Result<()> {
+    println!(
+        "{} encryption key for topic '{}' in OS keychain",
+        if encoded_key.is_empty() { "Removing" } else { "Saving" },
+        topic
+    );
+    set_secret(&topic_key_name(topic), encoded_key)
+}
+
+/// Keychain key holding the custom headers of a webhook sink (as a JSON object)
+fn webhook_headers_name(sink: &str) -> String {
+    format!("webhook_headers:{}", sink)
+}
+
+/// Load the custom headers for a webhook sink (empty if none are stored)
+pub fn load_webhook_headers(sink: &str) -> Result<Vec<(String, String)>> {
+    let json = get_secret(&webhook_headers_name(sink))?;
+    if json.is_empty() {
+        return Ok(Vec::new());
+    }
+    let headers: std::collections::BTreeMap<String, String> = serde_json::from_str(&json)
+        .map_err(|e| anyhow::anyhow!("Invalid stored headers for webhook '{}': {}", sink, e))?;
+    Ok(headers.into_iter().collect())
+}
+
+/// Save the custom headers for a webhook sink (no headers deletes the entry)
+pub fn save_webhook_headers(sink: &str, headers: &std::collections::BTreeMap<String, String>) -> Result<()> {
+    println!("Saving {} header(s) for webhook '{}' in OS keychain", headers.len(), sink);
+    let json = if headers.is_empty() { String::new() } else { serde_json::to_string(headers)? };
+    set_secret(&webhook_headers_name(sink), &json)
+}
+
+/// Keychain keys for the SMTP login used by the email sink
+const KEY_SMTP_USER: &str = "smtp_user";
+const KEY_SMTP_PASS: &str = "smtp_pass";
+
+/// Load the SMTP username and password (empty if none are stored)
+pub fn load_smtp_credentials() -> Result<(String, String)> {
+    Ok((get_secret(KEY_SMTP_USER)?, get_secret(KEY_SMTP_PASS)?))
+}
+
+/// Save the SMTP username and password (empty values delete them)
+pub fn save_smtp_credentials(user: &str, pass: &str) -> Result<()> {
+    println!(
+        "Saving SMTP credentials to OS keychain: user={}",
+        if user.is_empty() { "empty" } else { "present" }
+    );
+    set_secret(KEY_SMTP_USER, user)?;
+    set_secret(KEY_SMTP_PASS, pass)
+}
@@ -0,0 +1,160 @@
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Nonce};
+use anyhow::Result;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+
+/// Prefix marking an encrypted payload, so plaintext published by other clients
+/// to the same topic still passes through untouched
+pub const ENCRYPTED_PREFIX: &str = "ntfy-desktop:v1:";
+
+/// PBKDF2-HMAC-SHA256 iteration count (OWASP 2023 recommendation)
+pub const PBKDF2_ROUNDS: u32 = 600_000;
+
+/// AES-GCM standard nonce length
+const NONCE_LEN: usize = 12;
+
+/// 256-bit AES key derived from a topic passphrase
+pub type TopicKey = [u8; 32];
+
+/// Result of decrypting an incoming message on an encrypted topic
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum DecryptionStatus {
+    Decrypted,
+    Failed,
+}
+
+/// Derive the key for a topic from its passphrase.
+/// The salt is the topic name, so every subscriber sharing the passphrase derives the same key.
+pub fn derive_key(passphrase: &str, topic: &str) -> TopicKey {
+    derive_key_with_rounds(passphrase, topic, PBKDF2_ROUNDS)
+}
+
+/// `derive_key` with an explicit iteration count; lets tests use cheap keys
+pub fn derive_key_with_rounds(passphrase: &str, topic: &str, rounds: u32) -> TopicKey {
+    let salt = format!("ntfy-desktop:{}", topic);
+    let mut key = [0u8; 32];
+    pbkdf2::pbkdf2_hmac::<Sha256>(passphrase.as_bytes(), salt.as_bytes(), rounds, &mut key);
+    key
+}
+
+/// Encode a key for storage in the OS keychain
+pub fn encode_key(key: &TopicKey) -> String {
+    BASE64.encode(key)
+}
+
+/// Decode a key previously stored with `encode_key`
+pub fn decode_key(encoded: &str) -> Result<TopicKey> {
+    let bytes = BASE64
+        .decode(encoded.trim())
+        .map_err(|e| anyhow::anyhow!("Invalid topic key encoding: {}", e))?;
+    bytes
+        .try_into()
+        .map_err(|_| anyhow::anyhow!("Invalid topic key length"))
+}
+
+/// Check whether a text field carries an encrypted payload
+pub fn is_encrypted(text: &str) -> bool {
+    text.starts_with(ENCRYPTED_PREFIX)
+}
+
+/// Encrypt a text field: `{prefix}{base64(nonce || ciphertext)}`
+pub fn encrypt(key: &TopicKey, plaintext: &str) -> Result<String> {
+    let cipher = Aes256Gcm::new(key.into());
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(&nonce, plaintext.as_bytes())
+        .map_err(|e| anyhow::anyhow!("Encryption failed: {}", e))?;
+
+    let mut payload = Vec::with_capacity(NONCE_LEN + ciphertext.len());
+    payload.extend_from_slice(&nonce);
+    payload.extend_from_slice(&ciphertext);
+
+    Ok(format!("{}{}", ENCRYPTED_PREFIX, BASE64.encode(payload)))
+}
+
+/// Decrypt a text field produced by `encrypt`
+pub fn decrypt(key: &TopicKey, payload: &str) -> Result<String> {
+    let encoded = payload
+        .strip_prefix(ENCRYPTED_PREFIX)
+        .ok_or_else(|| anyhow::anyhow!("Payload is not encrypted"))?;
+    let bytes = BASE64
+        .decode(encoded.trim())
+        .map_err(|e| anyhow::anyhow!("Invalid encrypted payload: {}", e))?;
+
+    if bytes.len() <= NONCE_LEN {
+        return Err(anyhow::anyhow!("Encrypted payload too short"));
+    }
+
+    let (nonce, ciphertext) = bytes.split_at(NONCE_LEN);
+    let cipher = Aes256Gcm::new(key.into());
+    let plaintext = cipher
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|_| anyhow::anyhow!("Decryption failed (wrong passphrase or corrupted message)"))?;
+
+    String::from_utf8(plaintext).map_err(|e| anyhow::anyhow!("Decrypted payload is not UTF-8: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Unoptimized test builds take a while at the production count
+    fn test_key(passphrase: &str, topic: &str) -> TopicKey {
+        derive_key_with_rounds(passphrase, topic, 1_000)
+    }
+
+    #[test]
+    fn test_production_key_derivation() {
+        // Reference value from Python's hashlib.pbkdf2_hmac with the same parameters
+        let key = derive_key("correct horse battery staple", "deploys");
+        assert_eq!(encode_key(&key), "Y4LbUSSAUHkk4+vm5q1/c4LlK2Xp6G6efmuRKWlSr2c=");
+        assert_ne!(key, test_key("correct horse battery staple", "deploys"));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let key = test_key("correct horse battery staple", "deploys");
+        let encrypted = encrypt(&key, "Deployed v1.2.3 to prod-eu").unwrap();
+
+        assert!(is_encrypted(&encrypted));
+        assert!(!encrypted.contains("prod-eu"));
+        assert_eq!(decrypt(&key, &encrypted).unwrap(), "Deployed v1.2.3 to prod-eu");
+    }
+
+    #[test]
+    fn test_key_depends_on_passphrase_and_topic() {
+        let key = test_key("secret", "deploys");
+
+        assert_eq!(key, test_key("secret", "deploys"));
+        assert_ne!(key, test_key("secret", "alerts"));
+        assert_ne!(key, test_key("other", "deploys"));
+    }
+
+    #[test]
+    fn test_wrong_key_fails() {
+        let encrypted = encrypt(&test_key("secret", "deploys"), "hello").unwrap();
+
+        assert!(decrypt(&test_key("guess", "deploys"), &encrypted).is_err());
+    }
+
+    #[test]
+    fn test_malformed_payloads() {
+        let key = test_key("secret", "deploys");
+
+        assert!(decrypt(&key, "plain text").is_err());
+        assert!(decrypt(&key, &format!("{}not-base64!", ENCRYPTED_PREFIX)).is_err());
+        assert!(decrypt(&key, &format!("{}AAAA", ENCRYPTED_PREFIX)).is_err());
+    }
+
+    #[test]
+    fn test_key_encoding() {
+        let key = test_key("secret", "deploys");
+
+        assert_eq!(decode_key(&encode_key(&key)).unwrap(), key);
+        assert!(decode_key("c2hvcnQ=").is_err());
+    }
+}
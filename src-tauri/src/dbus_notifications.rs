@@ -0,0 +1,430 @@
+//! Native notifications on Linux through `org.freedesktop.Notifications`.
+//! See https://specifications.freedesktop.org/notification-spec/latest/
+
+use anyhow::Result;
+use futures_util::StreamExt;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::sync::{broadcast, OnceCell};
+use zbus::zvariant::Value;
+use zbus::Connection;
+
+use super::config::NotificationSound;
+use super::notifications::NotificationData;
+
+pub const NOTIFICATIONS_BUS_NAME: &str = "org.freedesktop.Notifications";
+pub const NOTIFICATIONS_PATH: &str = "/org/freedesktop/Notifications";
+
+/// Action key reported when the notification itself is clicked
+pub const DEFAULT_ACTION: &str = "default";
+
+const APP_NAME: &str = "ntfy";
+
+/// `NotificationClosed` reason for notifications closed through `CloseNotification`
+pub const CLOSED_BY_CALL: u32 = 3;
+
+#[zbus::proxy(interface = "org.freedesktop.Notifications", default_path = "/org/freedesktop/Notifications")]
+trait Notifications {
+    #[allow(clippy::too_many_arguments)]
+    fn notify(
+        &self,
+        app_name: &str,
+        replaces_id: u32,
+        app_icon: &str,
+        summary: &str,
+        body: &str,
+        actions: &[&str],
+        hints: HashMap<&str, Value<'_>>,
+        expire_timeout: i32,
+    ) -> zbus::Result<u32>;
+
+    fn close_notification(&self, id: u32) -> zbus::Result<()>;
+
+    #[zbus(signal)]
+    fn action_invoked(&self, id: u32, action_key: String) -> zbus::Result<()>;
+
+    #[zbus(signal)]
+    fn notification_closed(&self, id: u32, reason: u32) -> zbus::Result<()>;
+}
+
+/// What happened to a notification shown by this app
+#[derive(Debug, Clone)]
+pub enum NotificationEvent {
+    /// The notification (`DEFAULT_ACTION`) or one of its buttons was clicked
+    Action { id: u32, action: String, data: NotificationData },
+    Closed { id: u32, reason: u32, data: NotificationData },
+}
+
+/// Freedesktop sound theme name for a sound setting
+pub fn sound_name(sound: &NotificationSound) -> Option<&'static str> {
+    match sound {
+        // Custom files are played by the app itself (see sounds.rs)
+        NotificationSound::None | NotificationSound::Custom(_) => None,
+        NotificationSound::Default => Some("message-new-instant"),
+        NotificationSound::Alert => Some("dialog-warning"),
+        NotificationSound::Bell => Some("bell"),
+        NotificationSound::Chime => Some("complete"),
+        NotificationSound::Pop => Some("message"),
+    }
+}
+
+fn hints(data: &NotificationData) -> HashMap<&'static str, Value<'static>> {
+    let mut hints = HashMap::new();
+    // 0 = low, 1 = normal, 2 = critical
+    hints.insert("urgency", Value::from(if data.urgent { 2u8 } else { 1u8 }));
+    match sound_name(&data.sound) {
+        Some(name) => hints.insert("sound-name", Value::from(name)),
+        None => hints.insert("suppress-sound", Value::from(true)),
+    };
+    if data.persistent {
+        hints.insert("resident", Value::from(true));
+    }
+    hints
+}
+
+/// Milliseconds until the server closes the notification; 0 = never, -1 = server default
+fn expire_timeout(data: &NotificationData) -> i32 {
+    if data.persistent {
+        0
+    } else {
+        -1
+    }
+}
+
+/// Action keys and labels: the notification itself, then one `action-N` per button
+fn actions(data: &NotificationData) -> Vec<String> {
+    let mut actions = vec![DEFAULT_ACTION.to_string(), "Open".to_string()];
+    for (i, action) in data.actions.iter().enumerate() {
+        actions.push(format!("action-{}", i));
+        actions.push(action.label.clone());
+    }
+    actions
+}
+
+/// Button index for an `action-N` key
+pub fn action_index(key: &str) -> Option<usize> {
+    key.strip_prefix("action-")?.parse().ok()
+}
+
+/// Servers may render the body as markup, so the text is escaped
+fn escape_markup(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+/// Notifications currently on screen
+#[derive(Default)]
+struct Shown {
+    by_id: HashMap<u32, NotificationData>,
+    by_tag: HashMap<String, u32>,
+}
+
+/// Client for the session's notification server
+#[derive(Clone)]
+pub struct DbusNotifier {
+    proxy: NotificationsProxy<'static>,
+    shown: Arc<Mutex<Shown>>,
+    events: broadcast::Sender<NotificationEvent>,
+}
+
+static NOTIFIER: OnceCell<DbusNotifier> = OnceCell::const_new();
+
+impl DbusNotifier {
+    /// Notifier on the session bus, connected on first use
+    pub async fn shared() -> Result<&'static DbusNotifier> {
+        NOTIFIER
+            .get_or_try_init(|| async {
+                let connection = Connection::session()
+                    .await
+                    .map_err(|e| anyhow::anyhow!("No D-Bus session bus for notifications: {}", e))?;
+                let notifier = Self::connect(&connection, NOTIFICATIONS_BUS_NAME).await?;
+
+                // Clicks go to the app's shared activation channel
+                let mut events = notifier.subscribe();
+                let closer = notifier.clone();
+                tokio::spawn(async move {
+                    loop {
+                        match events.recv().await {
+                            Ok(NotificationEvent::Action { id, action, data }) => {
+                                let index = action_index(&action);
+                                if index.and_then(|i| data.actions.get(i)).and_then(|a| a.clear) == Some(true) {
+                                    let _ = closer.close(id).await;
+                                }
+                                if action == DEFAULT_ACTION || index.is_some() {
+                                    crate::notifications::notify_activated(data, index);
+                                }
+                            }
+                            Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => {}
+                            Err(broadcast::error::RecvError::Closed) => break,
+                        }
+                    }
+                });
+                Ok(notifier)
+            })
+            .await
+    }
+
+    /// Talk to the notification server at `destination` and start listening for its signals
+    pub async fn connect(connection: &Connection, destination: &str) -> Result<Self> {
+        let proxy = NotificationsProxy::builder(connection)
+            .destination(destination.to_string())?
+            .build()
+            .await?;
+        let (events, _) = broadcast::channel(32);
+        let notifier = Self { proxy, shown: Arc::new(Mutex::new(Shown::default())), events };
+
+        let mut actions = notifier.proxy.receive_action_invoked().await?;
+        let listener = notifier.clone();
+        tokio::spawn(async move {
+            while let Some(signal) = actions.next().await {
+                if let Ok(args) = signal.args() {
+                    listener.on_action(args.id, args.action_key);
+                }
+            }
+        });
+
+        let mut closed = notifier.proxy.receive_notification_closed().await?;
+        let listener = notifier.clone();
+        tokio::spawn(async move {
+            while let Some(signal) = closed.next().await {
+                if let Ok(args) = signal.args() {
+                    listener.on_closed(args.id, args.reason);
+                }
+            }
+        });
+
+        Ok(notifier)
+    }
+
+    /// Events for notifications shown by this notifier
+    pub fn subscribe(&self) -> broadcast::Receiver<NotificationEvent> {
+        self.events.subscribe()
+    }
+
+    fn shown(&self) -> std::sync::MutexGuard<'_, Shown> {
+        self.shown.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Show a notification, replacing the one with the same tag if it is still open. Returns its id.
+    pub async fn show(&self, data: &NotificationData, icon: Option<&str>) -> Result<u32> {
+        let replaces_id = data
+            .tag
+            .as_ref()
+            .and_then(|tag| self.shown().by_tag.get(tag).copied())
+            .unwrap_or(0);
+
+        let actions = actions(data);
+        let actions: Vec<&str> = actions.iter().map(String::as_str).collect();
+        let id = self
+            .proxy
+            .notify(
+                APP_NAME,
+                replaces_id,
+                icon.unwrap_or_default(),
+                &data.title,
+                &escape_markup(&data.message),
+                &actions,
+                hints(data),
+                expire_timeout(data),
+            )
+            .await
+            .map_err(|e| anyhow::anyhow!("Notification server rejected notification: {}", e))?;
+
+        let mut shown = self.shown();
+        if let Some(tag) = &data.tag {
+            shown.by_tag.insert(tag.clone(), id);
+        }
+        shown.by_id.insert(id, data.clone());
+        Ok(id)
+    }
+
+    pub async fn close(&self, id: u32) -> Result<()> {
+        Ok(self.proxy.close_notification(id).await?)
+    }
+
+    fn on_action(&self, id: u32, action: String) {
+        // Signals are broadcast for every app's notifications
+        let Some(data) = self.shown().by_id.get(&id).cloned() else { return };
+        let _ = self.events.send(NotificationEvent::Action { id, action, data });
+    }
+
+    fn on_closed(&self, id: u32, reason: u32) {
+        let data = {
+            let mut shown = self.shown();
+            shown.by_tag.retain(|_, shown_id| *shown_id != id);
+            shown.by_id.remove(&id)
+        };
+        if let Some(data) = data {
+            let _ = self.events.send(NotificationEvent::Closed { id, reason, data });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use zbus::object_server::SignalEmitter;
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Call {
+        replaces_id: u32,
+        app_icon: String,
+        summary: String,
+        body: String,
+        actions: Vec<String>,
+        urgency: u8,
+        sound: Option<String>,
+        expire_timeout: i32,
+    }
+
+    /// Notification server stand-in that records calls
+    #[derive(Default, Clone)]
+    struct FakeDaemon {
+        calls: Arc<Mutex<Vec<Call>>>,
+    }
+
+    #[zbus::interface(name = "org.freedesktop.Notifications")]
+    impl FakeDaemon {
+        #[allow(clippy::too_many_arguments)]
+        fn notify(
+            &self,
+            _app_name: String,
+            replaces_id: u32,
+            app_icon: String,
+            summary: String,
+            body: String,
+            actions: Vec<String>,
+            hints: HashMap<String, zbus::zvariant::OwnedValue>,
+            expire_timeout: i32,
+        ) -> u32 {
+            let mut calls = self.calls.lock().unwrap();
+            calls.push(Call {
+                replaces_id,
+                app_icon,
+                summary,
+                body,
+                actions,
+                urgency: hints.get("urgency").and_then(|v| u8::try_from(v).ok()).unwrap_or(1),
+                sound: hints.get("sound-name").and_then(|v| String::try_from(v.try_clone().ok()?).ok()),
+                expire_timeout,
+            });
+            if replaces_id != 0 {
+                replaces_id
+            } else {
+                calls.len() as u32
+            }
+        }
+
+        async fn close_notification(&self, id: u32, #[zbus(signal_emitter)] emitter: SignalEmitter<'_>) {
+            let _ = Self::notification_closed(&emitter, id, CLOSED_BY_CALL).await;
+        }
+
+        #[zbus(signal)]
+        async fn action_invoked(emitter: &SignalEmitter<'_>, id: u32, action_key: &str) -> zbus::Result<()>;
+
+        #[zbus(signal)]
+        async fn notification_closed(emitter: &SignalEmitter<'_>, id: u32, reason: u32) -> zbus::Result<()>;
+    }
+
+    fn data(title: &str, tag: Option<&str>) -> NotificationData {
+        NotificationData {
+            title: title.to_string(),
+            subtitle: None,
+            message: "disk <95%> & rising".to_string(),
+            topic: "alerts".to_string(),
+            timestamp: 0,
+            urgent: false,
+            sound: NotificationSound::Bell,
+            persistent: false,
+            icon_url: None,
+            tag: tag.map(String::from),
+            ..Default::default()
+        }
+    }
+
+    async fn next_event(events: &mut broadcast::Receiver<NotificationEvent>) -> NotificationEvent {
+        tokio::time::timeout(Duration::from_secs(5), events.recv()).await.unwrap().unwrap()
+    }
+
+    /// Skipped when no session bus is available (e.g. headless CI without dbus-run-session)
+    #[tokio::test]
+    async fn test_notifications_over_session_bus() {
+        if std::env::var_os("DBUS_SESSION_BUS_ADDRESS").is_none() {
+            eprintln!("Skipping: no D-Bus session bus");
+            return;
+        }
+
+        let daemon = FakeDaemon::default();
+        let daemon_conn = zbus::connection::Builder::session()
+            .unwrap()
+            .serve_at(NOTIFICATIONS_PATH, daemon.clone())
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+        let daemon_name = daemon_conn.unique_name().unwrap().to_string();
+
+        let app_conn = Connection::session().await.unwrap();
+        let notifier = DbusNotifier::connect(&app_conn, &daemon_name).await.unwrap();
+        let mut events = notifier.subscribe();
+
+        let first = notifier.show(&data("Disk", Some("disk")), Some("/tmp/icon.png")).await.unwrap();
+        let urgent = NotificationData {
+            urgent: true,
+            persistent: true,
+            sound: NotificationSound::None,
+            actions: vec![crate::ntfy::NtfyAction {
+                action: "view".to_string(),
+                label: "Status page".to_string(),
+                url: Some("https://status.example.com".to_string()),
+                ..Default::default()
+            }],
+            ..data("Down", None)
+        };
+        let second = notifier.show(&urgent, None).await.unwrap();
+        // Same tag: replaces the first notification
+        let updated = notifier.show(&data("Disk (2)", Some("disk")), None).await.unwrap();
+        assert_eq!(updated, first);
+
+        let calls = daemon.calls.lock().unwrap().clone();
+        assert_eq!(calls[0].summary, "Disk");
+        assert_eq!(calls[0].body, "disk &lt;95%&gt; &amp; rising");
+        assert_eq!(calls[0].app_icon, "/tmp/icon.png");
+        assert_eq!(calls[0].actions, vec![DEFAULT_ACTION, "Open"]);
+        assert_eq!((calls[0].urgency, calls[0].expire_timeout), (1, -1));
+        assert_eq!(calls[0].sound.as_deref(), Some("bell"));
+        assert_eq!((calls[1].urgency, calls[1].expire_timeout), (2, 0));
+        assert_eq!(calls[1].sound, None);
+        assert_eq!(calls[1].actions, vec![DEFAULT_ACTION, "Open", "action-0", "Status page"]);
+        assert_eq!(calls[2].replaces_id, first);
+
+        let emitter = SignalEmitter::new(&daemon_conn, NOTIFICATIONS_PATH).unwrap();
+        FakeDaemon::action_invoked(&emitter, second, DEFAULT_ACTION).await.unwrap();
+        match next_event(&mut events).await {
+            NotificationEvent::Action { id, action, data } => {
+                assert_eq!((id, action.as_str(), data.title.as_str()), (second, DEFAULT_ACTION, "Down"));
+            }
+            other => panic!("unexpected event {:?}", other),
+        }
+
+        FakeDaemon::action_invoked(&emitter, second, "action-0").await.unwrap();
+        match next_event(&mut events).await {
+            NotificationEvent::Action { action, .. } => assert_eq!(action_index(&action), Some(0)),
+            other => panic!("unexpected event {:?}", other),
+        }
+
+        // Ids of other apps' notifications are ignored
+        FakeDaemon::action_invoked(&emitter, 999, DEFAULT_ACTION).await.unwrap();
+        notifier.close(first).await.unwrap();
+        match next_event(&mut events).await {
+            NotificationEvent::Closed { id, reason, data } => {
+                assert_eq!((id, reason, data.title.as_str()), (first, CLOSED_BY_CALL, "Disk (2)"));
+            }
+            other => panic!("unexpected event {:?}", other),
+        }
+
+        // The tag is free again once its notification is closed
+        notifier.show(&data("Disk (3)", Some("disk")), None).await.unwrap();
+        assert_eq!(daemon.calls.lock().unwrap()[3].replaces_id, 0);
+    }
+}
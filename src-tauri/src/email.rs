@@ -0,0 +1,396 @@
+//! SMTP sink: forwards messages from selected topics by email, one email per
+//! message or as a digest every N minutes. The SMTP login lives in the OS
+//! keychain, see `credentials::load_smtp_credentials`.
+
+use anyhow::Result;
+use lettre::message::header::ContentType;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+use super::ntfy::NtfyMessage;
+use super::webhook::render_template;
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum SmtpSecurity {
+    /// Plain connection (local relays only)
+    None,
+    #[default]
+    StartTls,
+    /// Implicit TLS (usually port 465)
+    Tls,
+}
+
+/// Recipients for a set of topics
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct EmailRoute {
+    /// Topics sent to these recipients (empty = all topics)
+    pub topics: Vec<String>,
+    pub recipients: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct EmailSettings {
+    pub enabled: bool,
+    pub server: String,
+    pub port: u16,
+    pub security: SmtpSecurity,
+    pub from: String,
+    pub routes: Vec<EmailRoute>,
+    /// Placeholders as in webhook templates: `{topic}`, `{title}`, `{message}`, `{priority}`, …
+    pub subject_template: String,
+    pub body_template: String,
+    /// Send a digest every N minutes instead of one email per message (0 = no batching)
+    pub digest_minutes: u32,
+}
+
+impl Default for EmailSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            server: String::new(),
+            port: 587,
+            security: SmtpSecurity::StartTls,
+            from: String::new(),
+            routes: Vec::new(),
+            subject_template: "[{topic}] {title}".to_string(),
+            body_template: "{message}\n\nTopic: {topic}\nPriority: {priority}\nTags: {tags}".to_string(),
+            digest_minutes: 0,
+        }
+    }
+}
+
+impl EmailSettings {
+    /// Recipients for a topic across all matching routes, without duplicates
+    pub fn recipients_for(&self, topic: &str) -> Vec<String> {
+        let mut recipients: Vec<String> = Vec::new();
+        for route in self.routes.iter().filter(|r| r.topics.is_empty() || r.topics.iter().any(|t| t == topic)) {
+            for recipient in route.recipients.iter().map(|r| r.trim()).filter(|r| !r.is_empty()) {
+                if !recipients.iter().any(|r| r == recipient) {
+                    recipients.push(recipient.to_string());
+                }
+            }
+        }
+        recipients
+    }
+
+    /// Subject and body for a single message
+    pub fn compose(&self, msg: &NtfyMessage) -> (String, String) {
+        let mut subject = render_template(&self.subject_template, msg, false);
+        if msg.title.as_deref().map_or(true, str::is_empty) {
+            // "[alerts] " reads badly without a title
+            subject = subject.trim().to_string();
+            if subject.ends_with(']') || subject.is_empty() {
+                subject = format!("{} {}", subject, msg.message.as_deref().unwrap_or_default().lines().next().unwrap_or_default())
+                    .trim()
+                    .to_string();
+            }
+        }
+        (subject, render_template(&self.body_template, msg, false))
+    }
+
+    /// Subject and body for a digest of several messages
+    pub fn compose_digest(&self, messages: &[NtfyMessage]) -> (String, String) {
+        let noun = if messages.len() == 1 { "message" } else { "messages" };
+        let mut topics: Vec<&str> = messages.iter().filter_map(|m| m.topic.as_deref()).collect();
+        topics.sort_unstable();
+        topics.dedup();
+        let subject = format!("{} ntfy {} ({})", messages.len(), noun, topics.join(", "));
+
+        let body = messages
+            .iter()
+            .map(|msg| {
+                let (subject, body) = self.compose(msg);
+                format!("{}\n{}\n{}", subject, "-".repeat(subject.chars().count().min(72)), body)
+            })
+            .collect::<Vec<_>>()
+            .join("\n\n");
+        (subject, body)
+    }
+
+    fn transport(&self, login: Option<(String, String)>) -> Result<AsyncSmtpTransport<Tokio1Executor>> {
+        let builder = match self.security {
+            SmtpSecurity::None => AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&self.server),
+            SmtpSecurity::StartTls => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&self.server)?,
+            SmtpSecurity::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(&self.server)?,
+        };
+        let mut builder = builder.port(self.port).timeout(Some(Duration::from_secs(30)));
+        if let Some((user, pass)) = login.filter(|(user, _)| !user.is_empty()) {
+            builder = builder.credentials(Credentials::new(user, pass));
+        }
+        Ok(builder.build())
+    }
+
+    /// Send one email
+    pub async fn send(
+        &self,
+        login: Option<(String, String)>,
+        recipients: &[String],
+        subject: &str,
+        body: &str,
+    ) -> Result<()> {
+        if self.server.is_empty() || self.from.is_empty() {
+            return Err(anyhow::anyhow!("SMTP server and sender address are required"));
+        }
+        let mut builder = Message::builder()
+            .from(self.from.parse().map_err(|e| anyhow::anyhow!("Invalid sender '{}': {}", self.from, e))?)
+            .subject(subject)
+            .header(ContentType::TEXT_PLAIN);
+        for recipient in recipients {
+            builder = builder.to(recipient.parse().map_err(|e| anyhow::anyhow!("Invalid recipient '{}': {}", recipient, e))?);
+        }
+        let email = builder.body(body.to_string())?;
+
+        self.transport(login)?
+            .send(email)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to send email via {}: {}", self.server, e))?;
+        Ok(())
+    }
+}
+
+fn load_login() -> Option<(String, String)> {
+    match super::credentials::load_smtp_credentials() {
+        Ok(login) => Some(login),
+        Err(e) => {
+            eprintln!("Failed to load SMTP credentials: {}", e);
+            None
+        }
+    }
+}
+
+/// Sends messages by email from the polling loop, batching them when a digest interval is set
+pub struct EmailForwarder {
+    /// Messages waiting for the next digest, by recipient list
+    pending: BTreeMap<Vec<String>, Vec<NtfyMessage>>,
+    last_digest: Instant,
+}
+
+impl Default for EmailForwarder {
+    fn default() -> Self {
+        Self { pending: BTreeMap::new(), last_digest: Instant::now() }
+    }
+}
+
+impl EmailForwarder {
+    /// Send (or queue) a message for its topic's recipients
+    pub fn forward(&mut self, settings: &EmailSettings, msg: &NtfyMessage) {
+        if !settings.enabled {
+            return;
+        }
+        let mut recipients = settings.recipients_for(msg.topic.as_deref().unwrap_or_default());
+        if recipients.is_empty() {
+            return;
+        }
+        if settings.digest_minutes > 0 {
+            // Topics going to the same people share one digest
+            recipients.sort();
+            self.pending.entry(recipients).or_default().push(msg.clone());
+            return;
+        }
+
+        let settings = settings.clone();
+        let (subject, body) = settings.compose(msg);
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = settings.send(load_login(), &recipients, &subject, &body).await {
+                eprintln!("{}", e);
+            }
+        });
+    }
+
+    /// Digests that are due, by recipient list. Clears the queue.
+    pub fn take_due(&mut self, settings: &EmailSettings, now: Instant) -> Vec<(Vec<String>, Vec<NtfyMessage>)> {
+        let interval = Duration::from_secs(settings.digest_minutes as u64 * 60);
+        if now.duration_since(self.last_digest) < interval {
+            return Vec::new();
+        }
+        self.last_digest = now;
+        std::mem::take(&mut self.pending).into_iter().collect()
+    }
+
+    /// Send the digests that are due
+    pub fn flush(&mut self, settings: &EmailSettings) {
+        // Batching switched off (or email disabled) while messages were queued: send them now
+        let due = if settings.enabled && settings.digest_minutes > 0 {
+            self.take_due(settings, Instant::now())
+        } else {
+            std::mem::take(&mut self.pending).into_iter().collect()
+        };
+        if due.is_empty() {
+            return;
+        }
+
+        let settings = settings.clone();
+        tauri::async_runtime::spawn(async move {
+            let login = load_login();
+            for (recipients, messages) in due {
+                let (subject, body) = settings.compose_digest(&messages);
+                if let Err(e) = settings.send(login.clone(), &recipients, &subject, &body).await {
+                    eprintln!("{}", e);
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::TcpListener;
+
+    /// Minimal SMTP server recording the DATA of each email
+    async fn stand_in() -> (u16, Arc<Mutex<Vec<String>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let log = received.clone();
+
+        tokio::spawn(async move {
+            loop {
+                let Ok((stream, _)) = listener.accept().await else { return };
+                let (reader, mut writer) = stream.into_split();
+                let mut lines = BufReader::new(reader).lines();
+                writer.write_all(b"220 localhost ESMTP\r\n").await.unwrap();
+                while let Ok(Some(line)) = lines.next_line().await {
+                    let command = line.to_ascii_uppercase();
+                    let reply: &[u8] = if command.starts_with("EHLO") {
+                        b"250 localhost\r\n"
+                    } else if command.starts_with("DATA") {
+                        writer.write_all(b"354 go ahead\r\n").await.unwrap();
+                        let mut data = Vec::new();
+                        while let Ok(Some(line)) = lines.next_line().await {
+                            if line == "." {
+                                break;
+                            }
+                            data.push(line);
+                        }
+                        log.lock().unwrap().push(data.join("\n"));
+                        b"250 queued\r\n"
+                    } else if command.starts_with("QUIT") {
+                        writer.write_all(b"221 bye\r\n").await.unwrap();
+                        break;
+                    } else {
+                        b"250 ok\r\n"
+                    };
+                    writer.write_all(reply).await.unwrap();
+                }
+            }
+        });
+        (port, received)
+    }
+
+    fn message(topic: &str, title: &str, body: &str) -> NtfyMessage {
+        serde_json::from_value(serde_json::json!({
+            "id": "m1",
+            "time": 1700000000,
+            "topic": topic,
+            "title": title,
+            "message": body,
+            "priority": 4,
+            "tags": ["warning"],
+        }))
+        .unwrap()
+    }
+
+    fn settings() -> EmailSettings {
+        EmailSettings {
+            enabled: true,
+            server: "127.0.0.1".to_string(),
+            security: SmtpSecurity::None,
+            from: "ntfy <ntfy@example.com>".to_string(),
+            routes: vec![
+                EmailRoute { topics: vec!["alerts".to_string()], recipients: vec!["ops@example.com".to_string()] },
+                EmailRoute {
+                    topics: Vec::new(),
+                    recipients: vec!["boss@example.com".to_string(), "ops@example.com".to_string()],
+                },
+            ],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_recipients_per_topic() {
+        let mut settings = settings();
+        assert_eq!(settings.recipients_for("alerts"), vec!["ops@example.com", "boss@example.com"]);
+        settings.routes.pop();
+        assert_eq!(settings.recipients_for("alerts"), vec!["ops@example.com"]);
+        assert!(settings.recipients_for("backups").is_empty());
+    }
+
+    #[test]
+    fn test_templates() {
+        let settings = settings();
+        let (subject, body) = settings.compose(&message("alerts", "Disk full", "95% used"));
+        assert_eq!(subject, "[alerts] Disk full");
+        assert_eq!(body, "95% used\n\nTopic: alerts\nPriority: 4\nTags: warning");
+
+        // No title: the first line of the message completes the subject
+        let (subject, _) = settings.compose(&message("alerts", "", "Backup failed\ndetails"));
+        assert_eq!(subject, "[alerts] Backup failed");
+
+        let (subject, body) =
+            settings.compose_digest(&[message("alerts", "One", "1"), message("alerts", "Two", "2")]);
+        assert_eq!(subject, "2 ntfy messages (alerts)");
+        assert!(body.starts_with("[alerts] One\n------------\n1"));
+        assert!(body.contains("[alerts] Two"));
+
+        let (subject, _) = settings.compose_digest(&[
+            message("ci", "Failed", "1"),
+            message("backups", "Done", "2"),
+            message("ci", "Fixed", "3"),
+        ]);
+        assert_eq!(subject, "3 ntfy messages (backups, ci)");
+    }
+
+    #[tokio::test]
+    async fn test_send_over_smtp() {
+        let (port, received) = stand_in().await;
+        let settings = EmailSettings { port, ..settings() };
+        let (subject, body) = settings.compose(&message("alerts", "Disk full", "95% used"));
+
+        settings
+            .send(None, &["ops@example.com".to_string()], &subject, &body)
+            .await
+            .unwrap();
+
+        let received = received.lock().unwrap();
+        assert_eq!(received.len(), 1);
+        assert!(received[0].contains("Subject: [alerts] Disk full"));
+        assert!(received[0].contains("To: ops@example.com"));
+        assert!(received[0].contains("95% used"));
+    }
+
+    #[test]
+    fn test_digest_batching() {
+        let settings = EmailSettings { digest_minutes: 10, ..settings() };
+        let mut forwarder = EmailForwarder::default();
+        forwarder.forward(&settings, &message("alerts", "One", "1"));
+        forwarder.forward(&settings, &message("alerts", "Two", "2"));
+        forwarder.forward(&settings, &message("backups", "Three", "3"));
+        let boss_only = EmailSettings {
+            routes: vec![EmailRoute { topics: Vec::new(), recipients: vec!["boss@example.com".to_string()] }],
+            ..settings.clone()
+        };
+        forwarder.forward(&boss_only, &message("backups", "Four", "4"));
+
+        let start = forwarder.last_digest;
+        assert!(forwarder.take_due(&settings, start + Duration::from_secs(60)).is_empty());
+
+        // Same recipients (in any order) share a digest
+        let due = forwarder.take_due(&settings, start + Duration::from_secs(600));
+        assert_eq!(due.len(), 2);
+        assert_eq!(due[0].0, vec!["boss@example.com"]);
+        assert_eq!(due[0].1.len(), 1);
+        assert_eq!(due[1].0, vec!["boss@example.com", "ops@example.com"]);
+        assert_eq!(due[1].1.len(), 3);
+        assert!(forwarder.pending.is_empty());
+    }
+}
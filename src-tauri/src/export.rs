@@ -0,0 +1,277 @@
+//! Export of local message history (NDJSON, CSV, JSON) and NDJSON import.
+//! NDJSON lines are the messages exactly as ntfy sends them, so exports can be
+//! replayed into other tools or imported back.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, Write};
+use std::path::Path;
+
+use super::history::{HistoryEntry, HistoryStore};
+use super::ntfy::NtfyMessage;
+use super::search::SearchQuery;
+
+/// Entries fetched from the store per query while exporting
+const EXPORT_PAGE_SIZE: u64 = 1000;
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    #[default]
+    Ndjson,
+    Csv,
+    Json,
+}
+
+impl ExportFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Ndjson => "ndjson",
+            ExportFormat::Csv => "csv",
+            ExportFormat::Json => "json",
+        }
+    }
+}
+
+/// Which messages to export
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ExportFilter {
+    pub topic: Option<String>,
+    /// Inclusive lower bound on message time (unix seconds)
+    pub since: Option<u64>,
+    /// Exclusive upper bound on message time (unix seconds)
+    pub until: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ExportSummary {
+    pub path: String,
+    pub count: usize,
+}
+
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+pub struct ImportSummary {
+    /// Messages added to history
+    pub imported: usize,
+    /// Messages already in history, or non-message events (open, keepalive)
+    pub skipped: usize,
+    /// Lines that could not be parsed
+    pub invalid: usize,
+}
+
+/// All entries matching the filter, oldest first
+pub fn collect_entries(store: &HistoryStore, filter: &ExportFilter) -> Result<Vec<HistoryEntry>> {
+    let query = SearchQuery {
+        topics: filter.topic.iter().cloned().collect(),
+        since: filter.since,
+        until: filter.until,
+        ..Default::default()
+    };
+
+    let mut entries = Vec::new();
+    loop {
+        let (total, page) = store.search(&query, entries.len() as u64, EXPORT_PAGE_SIZE)?;
+        let done = page.is_empty() || entries.len() + page.len() >= total as usize;
+        entries.extend(page);
+        if done {
+            break;
+        }
+    }
+    entries.reverse();
+    Ok(entries)
+}
+
+/// Write entries in the given format
+pub fn write_entries<W: Write>(entries: &[HistoryEntry], format: ExportFormat, mut writer: W) -> Result<()> {
+    match format {
+        ExportFormat::Ndjson => {
+            for entry in entries {
+                writeln!(writer, "{}", entry.raw)?;
+            }
+        }
+        ExportFormat::Json => {
+            let messages = entries
+                .iter()
+                .map(|entry| serde_json::from_str(&entry.raw))
+                .collect::<serde_json::Result<Vec<serde_json::Value>>>()?;
+            serde_json::to_writer_pretty(&mut writer, &messages)?;
+            writeln!(writer)?;
+        }
+        ExportFormat::Csv => {
+            let mut csv = csv::Writer::from_writer(&mut writer);
+            csv.write_record(["id", "time", "datetime", "server", "topic", "priority", "title", "message", "tags"])?;
+            for entry in entries {
+                let datetime = chrono::DateTime::from_timestamp(entry.time as i64, 0)
+                    .map(|dt| dt.to_rfc3339())
+                    .unwrap_or_default();
+                csv.write_record([
+                    entry.id.as_str(),
+                    &entry.time.to_string(),
+                    &datetime,
+                    &entry.server,
+                    &entry.topic,
+                    &entry.priority.to_string(),
+                    entry.title.as_deref().unwrap_or_default(),
+                    entry.message.as_deref().unwrap_or_default(),
+                    &entry.tags.join(","),
+                ])?;
+            }
+            csv.flush()?;
+        }
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// Export matching history entries to a file, returning how many were written
+pub fn export_to_file(store: &HistoryStore, path: &Path, format: ExportFormat, filter: &ExportFilter) -> Result<usize> {
+    let entries = collect_entries(store, filter)?;
+    let file = std::fs::File::create(path)
+        .map_err(|e| anyhow::anyhow!("Failed to create {}: {}", path.display(), e))?;
+    write_entries(&entries, format, std::io::BufWriter::new(file))?;
+    Ok(entries.len())
+}
+
+/// Import NDJSON messages into history. Messages carry no server URL, so all
+/// of them are attributed to `server`. Imported messages are marked as read.
+pub fn import_ndjson<R: BufRead>(store: &HistoryStore, reader: R, server: &str) -> Result<ImportSummary> {
+    let mut summary = ImportSummary::default();
+
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let msg: NtfyMessage = match serde_json::from_str(&line) {
+            Ok(msg) => msg,
+            Err(_) => {
+                summary.invalid += 1;
+                continue;
+            }
+        };
+        if msg.id.is_none() || msg.event.as_deref().map_or(false, |event| event != "message") {
+            summary.skipped += 1;
+            continue;
+        }
+
+        if store.insert_with_state(server, &msg, true)? {
+            summary.imported += 1;
+        } else {
+            summary.skipped += 1;
+        }
+    }
+
+    Ok(summary)
+}
+
+/// Import an NDJSON file into history
+pub fn import_file(store: &HistoryStore, path: &Path, server: &str) -> Result<ImportSummary> {
+    let file = std::fs::File::open(path)
+        .map_err(|e| anyhow::anyhow!("Failed to open {}: {}", path.display(), e))?;
+    import_ndjson(store, std::io::BufReader::new(file), server)
+}
+
+/// Default export file name, e.g. `ntfy-history-20261018-143000.ndjson`
+pub fn default_file_name(format: ExportFormat) -> String {
+    format!("ntfy-history-{}.{}", chrono::Local::now().format("%Y%m%d-%H%M%S"), format.extension())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(id: &str, topic: &str, time: u64) -> NtfyMessage {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "time": time,
+            "event": "message",
+            "topic": topic,
+            "title": format!("Title {}", id),
+            "message": "line one, \"quoted\"\nline two",
+            "priority": 4,
+            "tags": ["warning", "db"],
+        }))
+        .unwrap()
+    }
+
+    fn store() -> HistoryStore {
+        let store = HistoryStore::open_in_memory().unwrap();
+        store.insert("https://ntfy.sh", &message("a", "alerts", 1000)).unwrap();
+        store.insert("https://ntfy.sh", &message("b", "deploys", 2000)).unwrap();
+        store.insert("https://ntfy.sh", &message("c", "alerts", 3000)).unwrap();
+        store
+    }
+
+    fn export(store: &HistoryStore, format: ExportFormat, filter: &ExportFilter) -> String {
+        let entries = collect_entries(store, filter).unwrap();
+        let mut out = Vec::new();
+        write_entries(&entries, format, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_filter_and_order() {
+        let store = store();
+        let ids = |filter: &ExportFilter| -> Vec<String> {
+            collect_entries(&store, filter).unwrap().into_iter().map(|e| e.id).collect()
+        };
+
+        assert_eq!(ids(&ExportFilter::default()), vec!["a", "b", "c"]);
+        assert_eq!(ids(&ExportFilter { topic: Some("alerts".to_string()), ..Default::default() }), vec!["a", "c"]);
+        assert_eq!(ids(&ExportFilter { since: Some(2000), until: Some(3000), ..Default::default() }), vec!["b"]);
+    }
+
+    #[test]
+    fn test_ndjson_round_trip() {
+        let ndjson = export(&store(), ExportFormat::Ndjson, &ExportFilter::default());
+        assert_eq!(ndjson.lines().count(), 3);
+
+        let target = HistoryStore::open_in_memory().unwrap();
+        let summary = import_ndjson(&target, ndjson.as_bytes(), "https://ntfy.sh").unwrap();
+        assert_eq!(summary, ImportSummary { imported: 3, skipped: 0, invalid: 0 });
+
+        let page = target.list(&Default::default(), 0, 10).unwrap();
+        assert_eq!(page.total, 3);
+        assert!(page.entries.iter().all(|e| e.read));
+        assert_eq!(page.entries[0].tags, vec!["warning", "db"]);
+
+        // Importing the same file again adds nothing
+        let summary = import_ndjson(&target, ndjson.as_bytes(), "https://ntfy.sh").unwrap();
+        assert_eq!(summary.imported, 0);
+        assert_eq!(summary.skipped, 3);
+    }
+
+    #[test]
+    fn test_import_skips_events_and_invalid_lines() {
+        let input = concat!(
+            "{\"id\":\"o1\",\"time\":1,\"event\":\"open\",\"topic\":\"alerts\"}\n",
+            "\n",
+            "not json\n",
+            "{\"id\":\"m1\",\"time\":2,\"event\":\"message\",\"topic\":\"alerts\",\"message\":\"hi\"}\n",
+        );
+        let store = HistoryStore::open_in_memory().unwrap();
+        let summary = import_ndjson(&store, input.as_bytes(), "https://ntfy.sh").unwrap();
+
+        assert_eq!(summary, ImportSummary { imported: 1, skipped: 1, invalid: 1 });
+    }
+
+    #[test]
+    fn test_csv_and_json() {
+        let store = store();
+        let filter = ExportFilter { topic: Some("deploys".to_string()), ..Default::default() };
+
+        let csv = export(&store, ExportFormat::Csv, &filter);
+        let mut reader = csv::Reader::from_reader(csv.as_bytes());
+        let records: Vec<csv::StringRecord> = reader.records().map(|r| r.unwrap()).collect();
+        assert_eq!(records.len(), 1);
+        assert_eq!(&records[0][0], "b");
+        assert_eq!(&records[0][2], "1970-01-01T00:33:20+00:00");
+        assert_eq!(&records[0][7], "line one, \"quoted\"\nline two");
+        assert_eq!(&records[0][8], "warning,db");
+
+        let json: serde_json::Value = serde_json::from_str(&export(&store, ExportFormat::Json, &filter)).unwrap();
+        assert_eq!(json[0]["id"], "b");
+        assert_eq!(json[0]["topic"], "deploys");
+    }
+}
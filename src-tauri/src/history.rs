@@ -0,0 +1,529 @@
+//! Local store of every received message (SQLite in the app data directory).
+//! Independent of the web UI, so it survives webview cache clears.
+
+use anyhow::Result;
+use rusqlite::{params, params_from_iter, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::Mutex;
+
+use super::ntfy::NtfyMessage;
+use super::search::SearchQuery;
+
+/// A received message as stored in local history
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct HistoryEntry {
+    pub id: String,
+    pub server: String,
+    pub topic: String,
+    pub time: u64,
+    pub received_at: u64,
+    pub priority: u8,
+    pub title: Option<String>,
+    pub message: Option<String>,
+    pub tags: Vec<String>,
+    pub read: bool,
+    /// Raw JSON of the message as received (after decryption)
+    pub raw: String,
+}
+
+impl HistoryEntry {
+    /// Parse the raw JSON back into the original message
+    pub fn to_message(&self) -> Result<NtfyMessage> {
+        serde_json::from_str(&self.raw).map_err(|e| anyhow::anyhow!("Invalid stored message {}: {}", self.id, e))
+    }
+}
+
+/// Identifies a stored message; ids are only unique per server
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct MessageKey {
+    pub server: String,
+    pub id: String,
+}
+
+/// Filter for listing history entries
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct HistoryFilter {
+    pub topic: Option<String>,
+    pub server: Option<String>,
+    pub unread_only: bool,
+}
+
+/// One page of history, newest first
+#[derive(Debug, Clone, Serialize)]
+pub struct HistoryPage {
+    pub total: u64,
+    pub offset: u64,
+    pub entries: Vec<HistoryEntry>,
+}
+
+pub struct HistoryStore {
+    conn: Mutex<Connection>,
+}
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS messages (
+        id          TEXT NOT NULL,
+        server      TEXT NOT NULL,
+        topic       TEXT NOT NULL,
+        time        INTEGER NOT NULL,
+        received_at INTEGER NOT NULL,
+        priority    INTEGER NOT NULL DEFAULT 3,
+        title       TEXT,
+        message     TEXT,
+        tags        TEXT NOT NULL DEFAULT '[]',
+        read        INTEGER NOT NULL DEFAULT 0,
+        raw         TEXT NOT NULL,
+        PRIMARY KEY (server, id)
+    );
+    CREATE INDEX IF NOT EXISTS idx_messages_time ON messages (time DESC);
+    CREATE INDEX IF NOT EXISTS idx_messages_topic ON messages (topic, time DESC);
+";
+
+const COLUMNS: &str = "id, server, topic, time, received_at, priority, title, message, tags, read, raw";
+
+fn now_secs() -> u64 {
+    chrono::Utc::now().timestamp().max(0) as u64
+}
+
+fn entry_from_row(row: &rusqlite::Row) -> rusqlite::Result<HistoryEntry> {
+    let tags: String = row.get(8)?;
+    Ok(HistoryEntry {
+        id: row.get(0)?,
+        server: row.get(1)?,
+        topic: row.get(2)?,
+        time: row.get::<_, i64>(3)?.max(0) as u64,
+        received_at: row.get::<_, i64>(4)?.max(0) as u64,
+        priority: row.get(5)?,
+        title: row.get(6)?,
+        message: row.get(7)?,
+        tags: serde_json::from_str(&tags).unwrap_or_default(),
+        read: row.get(9)?,
+        raw: row.get(10)?,
+    })
+}
+
+/// Escape LIKE wildcards so search terms match literally (used with `ESCAPE '\\'`)
+fn escape_like(term: &str) -> String {
+    term.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")
+}
+
+/// WHERE clause matching any of the given messages, with its parameters
+fn key_conditions(keys: &[MessageKey]) -> (String, Vec<rusqlite::types::Value>) {
+    let clause = vec!["(server = ? AND id = ?)"; keys.len()].join(" OR ");
+    let values = keys
+        .iter()
+        .flat_map(|key| [key.server.clone().into(), key.id.clone().into()])
+        .collect();
+    (clause, values)
+}
+
+impl HistoryStore {
+    /// Open (or create) the history database at the given path
+    pub fn open(path: &Path) -> Result<Self> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let conn = Connection::open(path)
+            .map_err(|e| anyhow::anyhow!("Failed to open history database {}: {}", path.display(), e))?;
+        Self::init(conn)
+    }
+
+    /// In-memory store (used by tests)
+    pub fn open_in_memory() -> Result<Self> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(conn: Connection) -> Result<Self> {
+        conn.execute_batch(SCHEMA)
+            .map_err(|e| anyhow::anyhow!("Failed to initialize history database: {}", e))?;
+        Ok(Self { conn: Mutex::new(conn) })
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Connection> {
+        // A panic while holding the lock leaves the connection itself usable
+        self.conn.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Store a received message. Returns false if it was already stored.
+    pub fn insert(&self, server: &str, msg: &NtfyMessage) -> Result<bool> {
+        self.insert_with_state(server, msg, false)
+    }
+
+    /// Store a message with an explicit read state
+    pub fn insert_with_state(&self, server: &str, msg: &NtfyMessage, read: bool) -> Result<bool> {
+        let Some(id) = msg.id.as_deref() else {
+            return Err(anyhow::anyhow!("Message has no id"));
+        };
+        let raw = serde_json::to_string(msg)?;
+        let tags = serde_json::to_string(&msg.tags.clone().unwrap_or_default())?;
+
+        let inserted = self.lock().execute(
+            "INSERT OR IGNORE INTO messages (id, server, topic, time, received_at, priority, title, message, tags, read, raw)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+            params![
+                id,
+                server,
+                msg.topic.as_deref().unwrap_or_default(),
+                msg.time as i64,
+                now_secs() as i64,
+                msg.priority.unwrap_or(3),
+                msg.title,
+                msg.message,
+                tags,
+                read,
+                raw,
+            ],
+        )?;
+        Ok(inserted > 0)
+    }
+
+    /// List entries newest first
+    pub fn list(&self, filter: &HistoryFilter, offset: u64, limit: u64) -> Result<HistoryPage> {
+        let mut clauses = Vec::new();
+        let mut values: Vec<rusqlite::types::Value> = Vec::new();
+        if let Some(topic) = &filter.topic {
+            clauses.push("topic = ?");
+            values.push(topic.clone().into());
+        }
+        if let Some(server) = &filter.server {
+            clauses.push("server = ?");
+            values.push(server.clone().into());
+        }
+        if filter.unread_only {
+            clauses.push("read = 0");
+        }
+        let (total, entries) = self.query_page(&clauses, values, offset, limit)?;
+        Ok(HistoryPage { total, offset, entries })
+    }
+
+    /// Entries matching a parsed search query, newest first, with the total match count
+    pub fn search(&self, query: &SearchQuery, offset: u64, limit: u64) -> Result<(u64, Vec<HistoryEntry>)> {
+        let mut clauses = Vec::new();
+        let mut values: Vec<rusqlite::types::Value> = Vec::new();
+
+        for term in &query.terms {
+            clauses.push("(title LIKE ? ESCAPE '\\' OR message LIKE ? ESCAPE '\\')");
+            let pattern = format!("%{}%", escape_like(term));
+            values.push(pattern.clone().into());
+            values.push(pattern.into());
+        }
+        if !query.topics.is_empty() {
+            clauses.push(match query.topics.len() {
+                1 => "topic = ?",
+                _ => "topic IN (SELECT value FROM json_each(?))",
+            });
+            values.push(match query.topics.len() {
+                1 => query.topics[0].clone(),
+                _ => serde_json::to_string(&query.topics)?,
+            }
+            .into());
+        }
+        for server in &query.servers {
+            clauses.push("server LIKE ? ESCAPE '\\'");
+            values.push(format!("%{}%", escape_like(server)).into());
+        }
+        for tag in &query.tags {
+            clauses.push("EXISTS (SELECT 1 FROM json_each(messages.tags) WHERE json_each.value = ?)");
+            values.push(tag.clone().into());
+        }
+        if let Some(min) = query.min_priority {
+            clauses.push("priority >= ?");
+            values.push(i64::from(min).into());
+        }
+        if let Some(max) = query.max_priority {
+            clauses.push("priority <= ?");
+            values.push(i64::from(max).into());
+        }
+        if let Some(since) = query.since {
+            clauses.push("time >= ?");
+            values.push((since as i64).into());
+        }
+        if let Some(until) = query.until {
+            clauses.push("time < ?");
+            values.push((until as i64).into());
+        }
+
+        self.query_page(&clauses, values, offset, limit)
+    }
+
+    /// Count and fetch one page of entries matching all clauses
+    fn query_page(
+        &self,
+        clauses: &[&str],
+        mut values: Vec<rusqlite::types::Value>,
+        offset: u64,
+        limit: u64,
+    ) -> Result<(u64, Vec<HistoryEntry>)> {
+        let where_clause = if clauses.is_empty() {
+            String::new()
+        } else {
+            format!("WHERE {}", clauses.join(" AND "))
+        };
+
+        let conn = self.lock();
+        let total: i64 = conn.query_row(
+            &format!("SELECT COUNT(*) FROM messages {}", where_clause),
+            params_from_iter(values.iter()),
+            |row| row.get(0),
+        )?;
+
+        values.push((limit as i64).into());
+        values.push((offset as i64).into());
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM messages {} ORDER BY time DESC, received_at DESC LIMIT ? OFFSET ?",
+            COLUMNS, where_clause
+        ))?;
+        let entries = stmt
+            .query_map(params_from_iter(values.iter()), entry_from_row)?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        Ok((total as u64, entries))
+    }
+
+    /// Look up a single entry by server and message id
+    pub fn get(&self, server: &str, id: &str) -> Result<Option<HistoryEntry>> {
+        let entry = self
+            .lock()
+            .query_row(
+                &format!("SELECT {} FROM messages WHERE server = ?1 AND id = ?2", COLUMNS),
+                params![server, id],
+                entry_from_row,
+            )
+            .optional()?;
+        Ok(entry)
+    }
+
+    /// Set the read state of the given messages
+    pub fn mark_read(&self, keys: &[MessageKey], read: bool) -> Result<usize> {
+        if keys.is_empty() {
+            return Ok(0);
+        }
+        let (clause, conditions) = key_conditions(keys);
+        let mut values: Vec<rusqlite::types::Value> = vec![read.into()];
+        values.extend(conditions);
+        let updated = self.lock().execute(
+            &format!("UPDATE messages SET read = ? WHERE {}", clause),
+            params_from_iter(values.iter()),
+        )?;
+        Ok(updated)
+    }
+
+    /// Mark every message as read
+    pub fn mark_all_read(&self) -> Result<usize> {
+        Ok(self.lock().execute("UPDATE messages SET read = 1 WHERE read = 0", [])?)
+    }
+
+    pub fn unread_count(&self) -> Result<u64> {
+        let count: i64 = self
+            .lock()
+            .query_row("SELECT COUNT(*) FROM messages WHERE read = 0", [], |row| row.get(0))?;
+        Ok(count as u64)
+    }
+
+    /// Messages received since the given time, counted per topic (most first)
+    pub fn topic_counts_since(&self, received_since: u64) -> Result<Vec<(String, u64)>> {
+        let conn = self.lock();
+        let mut stmt = conn.prepare(
+            "SELECT topic, COUNT(*) AS n FROM messages WHERE received_at >= ?1
+             GROUP BY topic ORDER BY n DESC, topic",
+        )?;
+        let counts = stmt
+            .query_map(params![received_since as i64], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?.max(0) as u64))
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(counts)
+    }
+
+    /// Delete the given messages
+    pub fn delete(&self, keys: &[MessageKey]) -> Result<usize> {
+        if keys.is_empty() {
+            return Ok(0);
+        }
+        let (clause, values) = key_conditions(keys);
+        let deleted = self.lock().execute(
+            &format!("DELETE FROM messages WHERE {}", clause),
+            params_from_iter(values.iter()),
+        )?;
+        Ok(deleted)
+    }
+
+    /// Delete every message
+    pub fn clear(&self) -> Result<usize> {
+        Ok(self.lock().execute("DELETE FROM messages", [])?)
+    }
+
+    /// Enforce retention limits. A limit of 0 disables it.
+    pub fn apply_retention(&self, max_messages: u32, max_age_days: u32) -> Result<usize> {
+        let conn = self.lock();
+        let mut deleted = 0;
+
+        if max_age_days > 0 {
+            let cutoff = now_secs().saturating_sub(max_age_days as u64 * 86400);
+            deleted += conn.execute("DELETE FROM messages WHERE time < ?1", params![cutoff as i64])?;
+        }
+
+        if max_messages > 0 {
+            deleted += conn.execute(
+                "DELETE FROM messages WHERE rowid NOT IN (
+                     SELECT rowid FROM messages ORDER BY time DESC, received_at DESC LIMIT ?1
+                 )",
+                params![max_messages],
+            )?;
+        }
+
+        Ok(deleted)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(id: &str, topic: &str, time: u64) -> NtfyMessage {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "time": time,
+            "event": "message",
+            "topic": topic,
+            "title": format!("Title {}", id),
+            "message": format!("Body {}", id),
+            "priority": 4,
+            "tags": ["warning", "db"],
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_insert_and_list() {
+        let store = HistoryStore::open_in_memory().unwrap();
+        let now = now_secs();
+
+        assert!(store.insert("https://ntfy.sh", &message("a", "alerts", now - 20)).unwrap());
+        assert!(store.insert("https://ntfy.sh", &message("b", "deploys", now - 10)).unwrap());
+        assert!(store.insert("https://ntfy.example.com", &message("c", "alerts", now)).unwrap());
+        // Duplicates are ignored
+        assert!(!store.insert("https://ntfy.sh", &message("a", "alerts", now - 20)).unwrap());
+
+        let page = store.list(&HistoryFilter::default(), 0, 10).unwrap();
+        assert_eq!(page.total, 3);
+        assert_eq!(page.entries.iter().map(|e| e.id.as_str()).collect::<Vec<_>>(), vec!["c", "b", "a"]);
+
+        let entry = &page.entries[2];
+        assert_eq!(entry.topic, "alerts");
+        assert_eq!(entry.priority, 4);
+        assert_eq!(entry.tags, vec!["warning", "db"]);
+        assert!(!entry.read);
+        assert_eq!(entry.to_message().unwrap().title.as_deref(), Some("Title a"));
+
+        let alerts = HistoryFilter { topic: Some("alerts".to_string()), ..Default::default() };
+        assert_eq!(store.list(&alerts, 0, 10).unwrap().total, 2);
+
+        let server = HistoryFilter { server: Some("https://ntfy.sh".to_string()), ..Default::default() };
+        assert_eq!(store.list(&server, 0, 10).unwrap().total, 2);
+    }
+
+    #[test]
+    fn test_paging() {
+        let store = HistoryStore::open_in_memory().unwrap();
+        let now = now_secs();
+        for i in 0..25 {
+            store.insert("https://ntfy.sh", &message(&format!("m{:02}", i), "alerts", now - 100 + i)).unwrap();
+        }
+
+        let first = store.list(&HistoryFilter::default(), 0, 10).unwrap();
+        let last = store.list(&HistoryFilter::default(), 20, 10).unwrap();
+
+        assert_eq!(first.total, 25);
+        assert_eq!(first.entries.len(), 10);
+        assert_eq!(first.entries[0].id, "m24");
+        assert_eq!(last.offset, 20);
+        assert_eq!(last.entries.len(), 5);
+        assert_eq!(last.entries[4].id, "m00");
+    }
+
+    #[test]
+    fn test_read_state_and_delete() {
+        let store = HistoryStore::open_in_memory().unwrap();
+        let now = now_secs();
+        for id in ["a", "b", "c"] {
+            store.insert("https://ntfy.sh", &message(id, "alerts", now)).unwrap();
+        }
+
+        assert_eq!(store.unread_count().unwrap(), 3);
+        assert_eq!(store.mark_read(&[key("a"), key("b")], true).unwrap(), 2);
+        assert_eq!(store.unread_count().unwrap(), 1);
+        assert!(store.get("https://ntfy.sh", "a").unwrap().unwrap().read);
+
+        let unread = HistoryFilter { unread_only: true, ..Default::default() };
+        assert_eq!(store.list(&unread, 0, 10).unwrap().entries[0].id, "c");
+
+        assert_eq!(store.mark_all_read().unwrap(), 1);
+        assert_eq!(store.unread_count().unwrap(), 0);
+
+        assert_eq!(store.delete(&[key("a")]).unwrap(), 1);
+        assert!(store.get("https://ntfy.sh", "a").unwrap().is_none());
+        assert_eq!(store.clear().unwrap(), 2);
+    }
+
+    fn key(id: &str) -> MessageKey {
+        MessageKey { server: "https://ntfy.sh".to_string(), id: id.to_string() }
+    }
+
+    #[test]
+    fn test_same_id_on_two_servers() {
+        let store = HistoryStore::open_in_memory().unwrap();
+        let now = now_secs();
+        store.insert("https://ntfy.sh", &message("a", "alerts", now)).unwrap();
+        store.insert("https://ntfy.example.com", &message("a", "backups", now)).unwrap();
+
+        assert_eq!(store.get("https://ntfy.example.com", "a").unwrap().unwrap().topic, "backups");
+        assert_eq!(store.mark_read(&[key("a")], true).unwrap(), 1);
+        assert!(!store.get("https://ntfy.example.com", "a").unwrap().unwrap().read);
+
+        assert_eq!(store.delete(&[key("a")]).unwrap(), 1);
+        assert!(store.get("https://ntfy.sh", "a").unwrap().is_none());
+        assert!(store.get("https://ntfy.example.com", "a").unwrap().is_some());
+    }
+
+    #[test]
+    fn test_retention() {
+        let store = HistoryStore::open_in_memory().unwrap();
+        let now = now_secs();
+        store.insert("https://ntfy.sh", &message("old", "alerts", now - 40 * 86400)).unwrap();
+        for i in 0..5 {
+            store.insert("https://ntfy.sh", &message(&format!("m{}", i), "alerts", now - 10 + i)).unwrap();
+        }
+
+        // Age limit first, then count limit keeps the newest
+        assert_eq!(store.apply_retention(0, 30).unwrap(), 1);
+        assert_eq!(store.apply_retention(3, 30).unwrap(), 2);
+
+        let ids: Vec<_> = store
+            .list(&HistoryFilter::default(), 0, 10)
+            .unwrap()
+            .entries
+            .into_iter()
+            .map(|e| e.id)
+            .collect();
+        assert_eq!(ids, vec!["m4", "m3", "m2"]);
+
+        // Zero disables both limits
+        assert_eq!(store.apply_retention(0, 0).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_persists_on_disk() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("nested").join("history.db");
+
+        HistoryStore::open(&path)
+            .unwrap()
+            .insert("https://ntfy.sh", &message("a", "alerts", now_secs()))
+            .unwrap();
+
+        assert!(HistoryStore::open(&path).unwrap().get("https://ntfy.sh", "a").unwrap().is_some());
+    }
+}
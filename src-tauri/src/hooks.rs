@@ -0,0 +1,303 @@
+//! Commands run for incoming messages (like `ntfy subscribe <topic> <command>`).
+//! The command line goes through the platform shell, message fields are passed
+//! as `NTFY_*` environment variables and the raw message JSON on stdin.
+
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::process::Stdio;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+use tokio::sync::Semaphore;
+
+use super::ntfy::NtfyMessage;
+
+/// Executions kept in the log
+const LOG_LIMIT: usize = 200;
+
+/// Output kept per execution (stdout and stderr combined)
+const OUTPUT_LIMIT: usize = 2048;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct HookCommand {
+    /// Command line, run with `sh -c` (`cmd /C` on Windows)
+    pub command: String,
+    /// Overrides the global hook timeout
+    #[serde(default)]
+    pub timeout_secs: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct HookSettings {
+    /// Hooks running at the same time; further executions wait
+    pub max_concurrent: usize,
+    pub timeout_secs: u64,
+}
+
+impl Default for HookSettings {
+    fn default() -> Self {
+        Self { max_concurrent: 4, timeout_secs: 30 }
+    }
+}
+
+/// One hook run, as shown in the execution log
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct HookExecution {
+    /// Start time (unix seconds)
+    pub time: u64,
+    pub command: String,
+    pub topic: String,
+    pub message_id: Option<String>,
+    pub exit_code: Option<i32>,
+    pub timed_out: bool,
+    pub duration_ms: u64,
+    pub output: String,
+    pub error: Option<String>,
+}
+
+impl HookExecution {
+    pub fn succeeded(&self) -> bool {
+        self.exit_code == Some(0)
+    }
+}
+
+/// Environment passed to hook commands
+pub fn environment(msg: &NtfyMessage) -> Vec<(&'static str, String)> {
+    vec![
+        ("NTFY_ID", msg.id.clone().unwrap_or_default()),
+        ("NTFY_TIME", msg.time.to_string()),
+        ("NTFY_TOPIC", msg.topic.clone().unwrap_or_default()),
+        ("NTFY_TITLE", msg.title.clone().unwrap_or_default()),
+        ("NTFY_MESSAGE", msg.message.clone().unwrap_or_default()),
+        ("NTFY_PRIORITY", msg.priority.unwrap_or(3).to_string()),
+        ("NTFY_TAGS", msg.tags.clone().unwrap_or_default().join(",")),
+        ("NTFY_CLICK", msg.click.clone().unwrap_or_default()),
+    ]
+}
+
+fn shell(command_line: &str) -> Command {
+    #[cfg(windows)]
+    {
+        let mut command = Command::new("cmd");
+        command.arg("/C").arg(command_line);
+        command
+    }
+    #[cfg(not(windows))]
+    {
+        let mut command = Command::new("sh");
+        command.arg("-c").arg(command_line);
+        command
+    }
+}
+
+fn truncate_output(stdout: &[u8], stderr: &[u8]) -> String {
+    let mut output = String::from_utf8_lossy(stdout).into_owned();
+    output.push_str(&String::from_utf8_lossy(stderr));
+    let output = output.trim();
+    if output.len() <= OUTPUT_LIMIT {
+        return output.to_string();
+    }
+    let mut end = OUTPUT_LIMIT;
+    while !output.is_char_boundary(end) {
+        end -= 1;
+    }
+    format!("{}…", &output[..end])
+}
+
+/// Runs hooks with a concurrency limit and keeps a log of recent executions
+#[derive(Clone)]
+pub struct HookRunner {
+    slots: Arc<Semaphore>,
+    log: Arc<Mutex<VecDeque<HookExecution>>>,
+    timeout: Duration,
+}
+
+impl HookRunner {
+    pub fn new(settings: &HookSettings) -> Self {
+        Self {
+            slots: Arc::new(Semaphore::new(settings.max_concurrent.max(1))),
+            log: Arc::new(Mutex::new(VecDeque::new())),
+            timeout: Duration::from_secs(settings.timeout_secs.max(1)),
+        }
+    }
+
+    /// Recent executions, newest first
+    pub fn log(&self) -> Vec<HookExecution> {
+        self.log.lock().unwrap_or_else(|e| e.into_inner()).iter().cloned().collect()
+    }
+
+    fn record(&self, execution: HookExecution) {
+        let mut log = self.log.lock().unwrap_or_else(|e| e.into_inner());
+        log.push_front(execution);
+        log.truncate(LOG_LIMIT);
+    }
+
+    /// Run a hook in the background
+    pub fn spawn(&self, hook: HookCommand, msg: NtfyMessage) {
+        let runner = self.clone();
+        tauri::async_runtime::spawn(async move {
+            runner.run(&hook, &msg).await;
+        });
+    }
+
+    /// Run a hook (waiting for a free slot) and log the result
+    pub async fn run(&self, hook: &HookCommand, msg: &NtfyMessage) -> HookExecution {
+        let _slot = self.slots.acquire().await;
+        let timeout = hook.timeout_secs.map(Duration::from_secs).unwrap_or(self.timeout);
+
+        let mut execution = HookExecution {
+            time: chrono::Utc::now().timestamp().max(0) as u64,
+            command: hook.command.clone(),
+            topic: msg.topic.clone().unwrap_or_default(),
+            message_id: msg.id.clone(),
+            exit_code: None,
+            timed_out: false,
+            duration_ms: 0,
+            output: String::new(),
+            error: None,
+        };
+        let started = Instant::now();
+
+        match self.execute(&hook.command, msg, timeout).await {
+            Ok(Some(output)) => {
+                execution.exit_code = output.status.code();
+                execution.output = truncate_output(&output.stdout, &output.stderr);
+            }
+            Ok(None) => {
+                execution.timed_out = true;
+                execution.error = Some(format!("Timed out after {}s", timeout.as_secs()));
+            }
+            Err(e) => execution.error = Some(e.to_string()),
+        }
+        execution.duration_ms = started.elapsed().as_millis() as u64;
+
+        if !execution.succeeded() {
+            eprintln!(
+                "Hook '{}' for topic '{}' failed: {}",
+                execution.command,
+                execution.topic,
+                execution
+                    .error
+                    .clone()
+                    .unwrap_or_else(|| format!("exit code {:?}", execution.exit_code))
+            );
+        }
+        self.record(execution.clone());
+        execution
+    }
+
+    /// Output of the finished command, or `None` when it was killed after the timeout
+    async fn execute(
+        &self,
+        command_line: &str,
+        msg: &NtfyMessage,
+        timeout: Duration,
+    ) -> anyhow::Result<Option<std::process::Output>> {
+        let mut command = shell(command_line);
+        command
+            .envs(environment(msg))
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+
+        let mut child = command
+            .spawn()
+            .map_err(|e| anyhow::anyhow!("Failed to start hook: {}", e))?;
+
+        let json = serde_json::to_vec(msg)?;
+        let stdin = child.stdin.take();
+        // The timeout covers writing stdin too: a command that never reads it
+        // would otherwise block the write once the pipe buffer is full
+        let run = async move {
+            if let Some(mut stdin) = stdin {
+                // The command may exit without reading stdin
+                let _ = stdin.write_all(&json).await;
+            }
+            child.wait_with_output().await
+        };
+
+        match tokio::time::timeout(timeout, run).await {
+            Ok(output) => Ok(Some(output?)),
+            Err(_) => Ok(None),
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    fn message() -> NtfyMessage {
+        serde_json::from_value(serde_json::json!({
+            "id": "abc",
+            "time": 1700000000,
+            "topic": "deploys",
+            "title": "Deployed",
+            "message": "v1.2.3 is live",
+            "priority": 4,
+            "tags": ["rocket", "prod"],
+        }))
+        .unwrap()
+    }
+
+    fn hook(command: &str) -> HookCommand {
+        HookCommand { command: command.to_string(), timeout_secs: None }
+    }
+
+    #[tokio::test]
+    async fn test_environment_and_stdin() {
+        let runner = HookRunner::new(&HookSettings::default());
+        let execution = runner
+            .run(
+                &hook(r#"echo "$NTFY_TOPIC|$NTFY_TITLE|$NTFY_MESSAGE|$NTFY_PRIORITY|$NTFY_TAGS"; cat"#),
+                &message(),
+            )
+            .await;
+
+        assert!(execution.succeeded(), "{:?}", execution);
+        let mut lines = execution.output.lines();
+        assert_eq!(lines.next(), Some("deploys|Deployed|v1.2.3 is live|4|rocket,prod"));
+        let stdin: serde_json::Value = serde_json::from_str(lines.next().unwrap()).unwrap();
+        assert_eq!(stdin["id"], "abc");
+        assert_eq!(runner.log(), vec![execution]);
+    }
+
+    #[tokio::test]
+    async fn test_failure_and_timeout_are_logged() {
+        let runner = HookRunner::new(&HookSettings::default());
+        let failed = runner.run(&hook("echo oops >&2; exit 3"), &message()).await;
+        assert_eq!(failed.exit_code, Some(3));
+        assert_eq!(failed.output, "oops");
+
+        let slow = HookCommand { command: "sleep 5".to_string(), timeout_secs: Some(1) };
+        let timed_out = runner.run(&slow, &message()).await;
+        assert!(timed_out.timed_out);
+        assert!(timed_out.duration_ms < 4000);
+
+        // More JSON than a pipe buffer holds, for a command that never reads stdin
+        let mut large = message();
+        large.icon = Some("x".repeat(1 << 20));
+        let stuck = runner.run(&slow, &large).await;
+        assert!(stuck.timed_out);
+        assert!(stuck.duration_ms < 4000);
+
+        let log = runner.log();
+        assert_eq!(log.len(), 3);
+        assert!(log[0].timed_out);
+    }
+
+    #[tokio::test]
+    async fn test_concurrency_limit() {
+        let runner = HookRunner::new(&HookSettings { max_concurrent: 1, timeout_secs: 10 });
+        let slow = hook("sleep 0.3");
+        let msg = message();
+
+        let started = Instant::now();
+        let (a, b) = tokio::join!(runner.run(&slow, &msg), runner.run(&slow, &msg));
+        assert!(a.succeeded() && b.succeeded());
+        assert!(started.elapsed() >= Duration::from_millis(600));
+    }
+}
@@ -0,0 +1,170 @@
+//! System log sink (Linux): messages of selected topics are written to the
+//! journal with structured `NTFY_*` fields, or to syslog when journald is not
+//! running.
+
+use anyhow::Result;
+use std::os::unix::net::UnixDatagram;
+use std::path::PathBuf;
+
+use super::ntfy::NtfyMessage;
+
+const JOURNAL_SOCKET: &str = "/run/systemd/journal/socket";
+const SYSLOG_SOCKET: &str = "/dev/log";
+const IDENTIFIER: &str = "ntfy-desktop";
+
+/// syslog facility "user"
+const FACILITY_USER: u8 = 1;
+
+/// Where a message ended up
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LogTarget {
+    Journal,
+    Syslog,
+}
+
+/// Syslog severity for an ntfy priority (urgent = crit … min = debug)
+pub fn severity(priority: u8) -> u8 {
+    match priority {
+        5..=u8::MAX => 2,
+        4 => 4,
+        3 => 5,
+        2 => 6,
+        _ => 7,
+    }
+}
+
+fn summary(msg: &NtfyMessage) -> String {
+    let topic = msg.topic.as_deref().unwrap_or_default();
+    let body = msg.message.as_deref().unwrap_or_default();
+    match msg.title.as_deref().filter(|t| !t.is_empty()) {
+        Some(title) => format!("[{}] {}: {}", topic, title, body),
+        None => format!("[{}] {}", topic, body),
+    }
+}
+
+/// Append a field in the journal's native format; values with newlines use the length-prefixed form
+fn push_field(buf: &mut Vec<u8>, name: &str, value: &str) {
+    buf.extend_from_slice(name.as_bytes());
+    if value.contains('\n') {
+        buf.push(b'\n');
+        buf.extend_from_slice(&(value.len() as u64).to_le_bytes());
+    } else {
+        buf.push(b'=');
+    }
+    buf.extend_from_slice(value.as_bytes());
+    buf.push(b'\n');
+}
+
+/// Journal entry for a message
+pub fn journal_entry(msg: &NtfyMessage) -> Vec<u8> {
+    let priority = msg.priority.unwrap_or(3);
+    let mut buf = Vec::new();
+    push_field(&mut buf, "MESSAGE", &summary(msg));
+    push_field(&mut buf, "PRIORITY", &severity(priority).to_string());
+    push_field(&mut buf, "SYSLOG_IDENTIFIER", IDENTIFIER);
+    push_field(&mut buf, "NTFY_TOPIC", msg.topic.as_deref().unwrap_or_default());
+    push_field(&mut buf, "NTFY_PRIORITY", &priority.to_string());
+    push_field(&mut buf, "NTFY_ID", msg.id.as_deref().unwrap_or_default());
+    buf
+}
+
+/// Syslog line for a message (the daemon adds the timestamp and host)
+pub fn syslog_line(msg: &NtfyMessage) -> String {
+    let pri = FACILITY_USER * 8 + severity(msg.priority.unwrap_or(3));
+    format!("<{}>{}[{}]: {}", pri, IDENTIFIER, std::process::id(), summary(msg).replace('\n', " "))
+}
+
+/// Writes messages to the journal, falling back to syslog
+pub struct JournalSink {
+    journal_socket: PathBuf,
+    syslog_socket: PathBuf,
+}
+
+impl Default for JournalSink {
+    fn default() -> Self {
+        Self::with_sockets(JOURNAL_SOCKET, SYSLOG_SOCKET)
+    }
+}
+
+impl JournalSink {
+    pub fn with_sockets(journal_socket: impl Into<PathBuf>, syslog_socket: impl Into<PathBuf>) -> Self {
+        Self { journal_socket: journal_socket.into(), syslog_socket: syslog_socket.into() }
+    }
+
+    pub fn write(&self, msg: &NtfyMessage) -> Result<LogTarget> {
+        let socket = UnixDatagram::unbound()?;
+        if self.journal_socket.exists() && socket.send_to(&journal_entry(msg), &self.journal_socket).is_ok() {
+            return Ok(LogTarget::Journal);
+        }
+        socket
+            .send_to(syslog_line(msg).as_bytes(), &self.syslog_socket)
+            .map_err(|e| anyhow::anyhow!("Failed to write to journal or syslog: {}", e))?;
+        Ok(LogTarget::Syslog)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(body: &str) -> NtfyMessage {
+        serde_json::from_value(serde_json::json!({
+            "id": "abc",
+            "time": 1700000000,
+            "topic": "backups",
+            "title": "Backup failed",
+            "message": body,
+            "priority": 5,
+        }))
+        .unwrap()
+    }
+
+    fn receive(socket: &UnixDatagram) -> Vec<u8> {
+        let mut buf = [0u8; 4096];
+        let n = socket.recv(&mut buf).unwrap();
+        buf[..n].to_vec()
+    }
+
+    #[test]
+    fn test_severity_mapping() {
+        assert_eq!([1, 2, 3, 4, 5].map(severity), [7, 6, 5, 4, 2]);
+    }
+
+    #[test]
+    fn test_journal_fields() {
+        let dir = tempfile::tempdir().unwrap();
+        let journal = UnixDatagram::bind(dir.path().join("journal")).unwrap();
+        let sink = JournalSink::with_sockets(dir.path().join("journal"), dir.path().join("log"));
+
+        assert_eq!(sink.write(&message("disk full")).unwrap(), LogTarget::Journal);
+        let entry = String::from_utf8(receive(&journal)).unwrap();
+        assert!(entry.contains("MESSAGE=[backups] Backup failed: disk full\n"));
+        assert!(entry.contains("PRIORITY=2\n"));
+        assert!(entry.contains("NTFY_TOPIC=backups\n"));
+        assert!(entry.contains("NTFY_PRIORITY=5\n"));
+        assert!(entry.contains("NTFY_ID=abc\n"));
+
+        // Multi-line values are length-prefixed
+        let entry = journal_entry(&message("line 1\nline 2"));
+        let value = "[backups] Backup failed: line 1\nline 2";
+        let mut expected = b"MESSAGE\n".to_vec();
+        expected.extend_from_slice(&(value.len() as u64).to_le_bytes());
+        expected.extend_from_slice(value.as_bytes());
+        assert!(entry.starts_with(&expected));
+    }
+
+    #[test]
+    fn test_falls_back_to_syslog() {
+        let dir = tempfile::tempdir().unwrap();
+        let syslog = UnixDatagram::bind(dir.path().join("log")).unwrap();
+        let sink = JournalSink::with_sockets(dir.path().join("missing"), dir.path().join("log"));
+
+        assert_eq!(sink.write(&message("line 1\nline 2")).unwrap(), LogTarget::Syslog);
+        let line = String::from_utf8(receive(&syslog)).unwrap();
+        assert!(line.starts_with("<10>ntfy-desktop["));
+        assert!(line.ends_with("]: [backups] Backup failed: line 1 line 2"));
+
+        let nowhere = JournalSink::with_sockets(dir.path().join("missing"), dir.path().join("missing"));
+        assert!(nowhere.write(&message("x")).is_err());
+    }
+}
@@ -1,8 +1,35 @@
+pub mod actions;
+pub mod alarm;
+pub mod badge;
+pub mod burst;
 pub mod config;
 pub mod credentials;
+pub mod crypto;
+#[cfg(target_os = "linux")]
+pub mod dbus_notifications;
+pub mod email;
+pub mod export;
+pub mod history;
+pub mod hooks;
+#[cfg(target_os = "linux")]
+pub mod journal;
+pub mod notification_center;
 pub mod notifications;
 pub mod ntfy;
+pub mod pause;
 pub mod performance;
+pub mod polling_state;
+pub mod priority;
+pub mod quiet_hours;
+pub mod rate_limit;
+pub mod rules;
+pub mod search;
+pub mod sounds;
+pub mod subscriptions;
+pub mod tags;
+#[cfg(target_os = "linux")]
+pub mod unifiedpush;
+pub mod webhook;
 
 pub use config::{AppConfig, NotificationSound, PersistentNotificationMode};
 pub use notifications::NotificationManager;
@@ -1,11 +1,38 @@
 // Prevents additional console window on Windows in release, DO NOT REMOVE!!
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+mod actions;
+mod alarm;
+mod badge;
+mod burst;
 mod config;
 mod credentials;
+mod crypto;
+#[cfg(target_os = "linux")]
+mod dbus_notifications;
+mod email;
+mod export;
+mod history;
+mod hooks;
+#[cfg(target_os = "linux")]
+mod journal;
+mod notification_center;
 mod notifications;
 mod ntfy;
+mod pause;
 mod performance;
+mod polling_state;
+mod priority;
+mod quiet_hours;
+mod rate_limit;
+mod rules;
+mod search;
+mod sounds;
+mod subscriptions;
+mod tags;
+#[cfg(target_os = "linux")]
+mod unifiedpush;
+mod webhook;
 
 /// Initialize Windows notification registry for unpackaged apps.
 /// This is required for toast notifications to work when running directly from exe.
@@ -80,19 +107,72 @@ use tauri::{
     image::Image,
     menu::{MenuBuilder, MenuItemBuilder},
     tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent},
-    Listener, Manager, RunEvent, WebviewUrl, WindowEvent,
+    Emitter, Listener, Manager, RunEvent, WebviewUrl, WindowEvent,
 };
 
 /// Shared config state for runtime updates (accessible from both Tauri commands and polling)
 struct SharedConfig(Arc<Mutex<config::AppConfig>>);
 
+/// Messages received since the user last viewed them (shown on the tray icon)
+struct UnreadBadge(Arc<AtomicU32>);
+
+/// Set while the tray icon is flashing, so overlapping flashes don't fight over the icon
+#[derive(Default)]
+struct TrayFlash(Arc<AtomicBool>);
+
+/// Click URL from an untrusted topic waiting for confirmation in the confirm-link window
+#[derive(Debug, Clone, serde::Serialize)]
+struct PendingLink {
+    url: String,
+    topic: String,
+}
+
+#[derive(Default)]
+struct PendingLinkState(std::sync::Mutex<Option<PendingLink>>);
+
+const CONFIRM_LINK_WINDOW: &str = "confirm-link";
+
+/// Label (or label prefix) of the settings windows, the only ones that may change commands
+const SETTINGS_WINDOW: &str = "settings";
+
+/// Always-on-top window listing alarms waiting for acknowledgement
+const ALARM_WINDOW: &str = "alarm";
+
+/// Seconds between runs of the alarm loop (repeats, snooze ends, tray flashing)
+const ALARM_TICK_SECS: u64 = 5;
+
+/// What the alarm window shows
+#[derive(Debug, Clone, serde::Serialize)]
+struct AlarmWindowState {
+    alarms: Vec<alarm::Alarm>,
+    /// 0 when snoozing is disabled
+    snooze_minutes: u64,
+}
+
 // ── Tauri Commands ──────────────────────────────────────────────────────────
 
 #[tauri::command]
 async fn save_config(
     config: config::AppConfig,
+    window: tauri::WebviewWindow,
     app_handle: tauri::AppHandle,
 ) -> Result<(), String> {
+    // Hooks run through the shell; the main window shows the (remote) ntfy web UI
+    if !window.label().starts_with(SETTINGS_WINDOW) {
+        let unchanged = match app_handle.try_state::<SharedConfig>() {
+            Some(shared) => shared.0.lock().await.commands() == config.commands(),
+            None => config.commands().is_empty(),
+        };
+        if !unchanged {
+            return Err("Commands can only be changed from Settings".to_string());
+        }
+    }
+    config.quiet_hours.validate().map_err(|e| e.to_string())?;
+    rules::RuleSet::compile(&config.rules).map_err(|e| e.to_string())?;
+    for path in config.custom_sounds() {
+        sounds::validate(std::path::Path::new(path)).map_err(|e| e.to_string())?;
+    }
+
     // Merge incoming credentials with existing keychain values:
     // empty incoming value = keep existing (prevents non-token pages from wiping creds)
     let existing = credentials::load_credentials().unwrap_or_default();
@@ -150,6 +230,7 @@ async fn load_config(app_handle: tauri::AppHandle) -> Result<config::AppConfig,
     Ok(config)
 }
 
+#[allow(clippy::too_many_arguments)]
 #[tauri::command(rename_all = "snake_case")]
 async fn show_notification(
     title: String,
@@ -158,9 +239,19 @@ async fn show_notification(
     sound: Option<String>,
     persistent: Option<bool>,
     icon_url: Option<String>,
+    topic: Option<String>,
+    app_handle: tauri::AppHandle,
 ) -> Result<(), String> {
     use crate::config::NotificationSound;
 
+    // Subscribed topics are shown (and decrypted) by start_polling, which applies rules,
+    // pause, mutes, quiet hours, priority policies, bursts and rate limits
+    if let (Some(topic), Some(shared)) = (topic.as_deref(), app_handle.try_state::<SharedConfig>()) {
+        if shared.0.lock().await.subscription(topic).is_some() {
+            return Ok(());
+        }
+    }
+
     let sound = match sound.as_deref() {
         Some("none") => NotificationSound::None,
         Some("alert") => NotificationSound::Alert,
@@ -186,11 +277,21 @@ async fn show_notification(
 }
 
 #[tauri::command]
-async fn preview_notification_sound(sound: String, urgent: Option<bool>) -> Result<(), String> {
-    println!("preview_notification_sound called: sound={}, urgent={:?}", sound, urgent);
+async fn preview_notification_sound(
+    sound: String,
+    urgent: Option<bool>,
+    path: Option<String>,
+) -> Result<(), String> {
+    println!("preview_notification_sound called: sound={}, urgent={:?}, path={:?}", sound, urgent, path);
     use crate::config::NotificationSound;
 
     let sound = match sound.as_str() {
+        "custom" => {
+            // Play the file here so a broken file is reported instead of a silent toast
+            let path = path.filter(|p| !p.trim().is_empty()).ok_or("No sound file selected")?;
+            sounds::play(std::path::Path::new(path.trim())).map_err(|e| e.to_string())?;
+            NotificationSound::None
+        }
         "none" => NotificationSound::None,
         "alert" => NotificationSound::Alert,
         "bell" => NotificationSound::Bell,
@@ -271,6 +372,307 @@ async fn test_ntfy_connection(
         .map_err(|e| e.to_string())
 }
 
+#[tauri::command]
+async fn publish_message(
+    topic: String,
+    message: String,
+    title: Option<String>,
+    priority: Option<u8>,
+    tags: Option<Vec<String>>,
+    app_handle: tauri::AppHandle,
+) -> Result<(), String> {
+    let config = match app_handle.try_state::<SharedConfig>() {
+        Some(shared) => shared.0.lock().await.clone(),
+        None => load_config_sync(&app_handle),
+    };
+
+    let mut request = ntfy::PublishRequest {
+        topic: topic.clone(),
+        message,
+        title: title.filter(|t| !t.is_empty()),
+        priority,
+        tags: tags.filter(|t| !t.is_empty()),
+    };
+
+    // Encrypt title and body for encrypted topics; priority and tags stay readable for routing
+    if config.is_topic_encrypted(&topic) {
+        let encoded = credentials::load_topic_key(&topic).map_err(|e| e.to_string())?;
+        if encoded.is_empty() {
+            return Err(format!("No encryption key stored for topic '{}'", topic));
+        }
+        let key = crypto::decode_key(&encoded).map_err(|e| e.to_string())?;
+        request.message = crypto::encrypt(&key, &request.message).map_err(|e| e.to_string())?;
+        if let Some(title) = request.title.take() {
+            request.title = Some(crypto::encrypt(&key, &title).map_err(|e| e.to_string())?);
+        }
+    }
+
+    let mut client = NtfyClient::new(&config.api_base_url());
+    if !config.api_token.is_empty() {
+        client = client.with_token(config.api_token.clone());
+    } else if !config.auth_user.is_empty() {
+        client = client.with_basic_auth(config.auth_user.clone(), config.auth_pass.clone());
+    }
+    client.publish(&request).await.map_err(|e| e.to_string())
+}
+
+/// Lets the web UI bridge route publishes on encrypted topics through `publish_message`
+#[tauri::command]
+async fn is_topic_encrypted(topic: String, app_handle: tauri::AppHandle) -> bool {
+    match app_handle.try_state::<SharedConfig>() {
+        Some(shared) => shared.0.lock().await.is_topic_encrypted(&topic),
+        None => load_config_sync(&app_handle).is_topic_encrypted(&topic),
+    }
+}
+
+/// Enable encryption for a topic with the given passphrase, or disable it when none is given
+#[tauri::command]
+async fn set_topic_encryption(
+    topic: String,
+    passphrase: Option<String>,
+    app_handle: tauri::AppHandle,
+) -> Result<(), String> {
+    let topic = topic.trim().to_string();
+    if topic.is_empty() {
+        return Err("Topic must not be empty".to_string());
+    }
+
+    // Key derivation is deliberately slow; keep it off the async runtime
+    let passphrase = passphrase.filter(|p| !p.is_empty());
+    let encoded_key = match passphrase {
+        Some(passphrase) => {
+            let topic = topic.clone();
+            tauri::async_runtime::spawn_blocking(move || crypto::encode_key(&crypto::derive_key(&passphrase, &topic)))
+                .await
+                .map_err(|e| e.to_string())?
+        }
+        None => String::new(),
+    };
+    credentials::save_topic_key(&topic, &encoded_key).map_err(|e| e.to_string())?;
+
+    let shared = app_handle
+        .try_state::<SharedConfig>()
+        .ok_or("Config not initialized")?;
+    let mut config = shared.0.lock().await;
+    config.encrypted_topics.retain(|t| t != &topic);
+    if !encoded_key.is_empty() {
+        config.encrypted_topics.push(topic);
+    }
+    config::save_config(&app_handle, config.clone())
+        .await
+        .map_err(|e| e.to_string())
+}
+
+fn history_store(app_handle: &tauri::AppHandle) -> Result<tauri::State<'_, history::HistoryStore>, String> {
+    app_handle
+        .try_state::<history::HistoryStore>()
+        .ok_or_else(|| "Message history is not available".to_string())
+}
+
+#[tauri::command]
+async fn list_history(
+    filter: Option<history::HistoryFilter>,
+    offset: Option<u64>,
+    limit: Option<u64>,
+    app_handle: tauri::AppHandle,
+) -> Result<history::HistoryPage, String> {
+    history_store(&app_handle)?
+        .list(&filter.unwrap_or_default(), offset.unwrap_or(0), limit.unwrap_or(50).min(500))
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn mark_history_read(
+    messages: Vec<history::MessageKey>,
+    read: Option<bool>,
+    app_handle: tauri::AppHandle,
+) -> Result<usize, String> {
+    history_store(&app_handle)?
+        .mark_read(&messages, read.unwrap_or(true))
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn delete_history(messages: Vec<history::MessageKey>, app_handle: tauri::AppHandle) -> Result<usize, String> {
+    history_store(&app_handle)?.delete(&messages).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn list_notification_center(
+    filter: Option<history::HistoryFilter>,
+    offset: Option<u64>,
+    limit: Option<u64>,
+    app_handle: tauri::AppHandle,
+) -> Result<notification_center::CenterPage, String> {
+    let store = history_store(&app_handle)?;
+    notification_center::list(&store, &filter.unwrap_or_default(), offset.unwrap_or(0), limit.unwrap_or(50).min(500))
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn mark_all_history_read(app_handle: tauri::AppHandle) -> Result<usize, String> {
+    history_store(&app_handle)?.mark_all_read().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn open_notification_center(app_handle: tauri::AppHandle) -> Result<(), String> {
+    open_notification_center_window(&app_handle).map_err(|e| e.to_string())
+}
+
+/// Show which rules would match a message and what they would change.
+/// Uses the saved rules unless `rules` is given (e.g. while editing).
+#[tauri::command]
+async fn dry_run_rules(
+    message: ntfy::NtfyMessage,
+    rules: Option<Vec<rules::Rule>>,
+    app_handle: tauri::AppHandle,
+) -> Result<rules::RuleOutcome, String> {
+    let rules = match (rules, app_handle.try_state::<SharedConfig>()) {
+        (Some(rules), _) => rules,
+        (None, Some(shared)) => shared.0.lock().await.rules.clone(),
+        (None, None) => Vec::new(),
+    };
+    let rule_set = rules::RuleSet::compile(&rules).map_err(|e| e.to_string())?;
+    Ok(rule_set.evaluate(&message))
+}
+
+/// Store custom headers (e.g. an Authorization token) for a webhook sink in the keychain
+#[tauri::command]
+async fn set_webhook_headers(name: String, headers: std::collections::BTreeMap<String, String>) -> Result<(), String> {
+    credentials::save_webhook_headers(&name, &headers).map_err(|e| e.to_string())
+}
+
+/// Store the SMTP login for the email sink in the keychain
+#[tauri::command]
+async fn set_smtp_credentials(user: String, pass: String) -> Result<(), String> {
+    credentials::save_smtp_credentials(&user, &pass).map_err(|e| e.to_string())
+}
+
+/// Send a test email to every configured recipient, using the (possibly unsaved) settings
+#[tauri::command]
+async fn send_test_email(settings: email::EmailSettings) -> Result<(), String> {
+    let mut recipients: Vec<String> = settings.routes.iter().flat_map(|r| r.recipients.iter().cloned()).collect();
+    recipients.sort();
+    recipients.dedup();
+    if recipients.is_empty() {
+        return Err("No recipients configured".to_string());
+    }
+    let login = credentials::load_smtp_credentials().map_err(|e| e.to_string())?;
+    settings
+        .send(Some(login), &recipients, "ntfy-desktop test email", "Email forwarding is set up correctly.")
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Recent hook executions, newest first
+#[tauri::command]
+fn hook_log(app_handle: tauri::AppHandle) -> Vec<hooks::HookExecution> {
+    app_handle.try_state::<hooks::HookRunner>().map(|runner| runner.log()).unwrap_or_default()
+}
+
+#[tauri::command]
+async fn search_messages(
+    query: String,
+    offset: Option<u64>,
+    limit: Option<u64>,
+    app_handle: tauri::AppHandle,
+) -> Result<search::SearchPage, String> {
+    let store = history_store(&app_handle)?;
+    search::search(&store, &query, offset.unwrap_or(0), limit.unwrap_or(50).min(500)).map_err(|e| e.to_string())
+}
+
+/// Directory for exports when no path is given: Downloads, falling back to the app data dir
+fn default_export_dir(app_handle: &tauri::AppHandle) -> Result<std::path::PathBuf, String> {
+    app_handle
+        .path()
+        .download_dir()
+        .or_else(|_| app_handle.path().app_data_dir())
+        .map_err(|e| e.to_string())
+}
+
+/// Wait for a native file dialog; None when it was cancelled
+async fn file_dialog(
+    dialog: tauri_plugin_dialog::FileDialogBuilder<tauri::Wry>,
+    save: bool,
+) -> Result<Option<std::path::PathBuf>, String> {
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    let done = move |path: Option<tauri_plugin_dialog::FilePath>| {
+        let _ = tx.send(path);
+    };
+    if save {
+        dialog.save_file(done);
+    } else {
+        dialog.pick_file(done);
+    }
+    match rx.await.map_err(|e| e.to_string())? {
+        Some(path) => path.into_path().map(Some).map_err(|e| e.to_string()),
+        None => Ok(None),
+    }
+}
+
+/// Export history to a file chosen in the native save dialog; None when cancelled
+#[tauri::command]
+async fn export_history(
+    format: Option<export::ExportFormat>,
+    filter: Option<export::ExportFilter>,
+    app_handle: tauri::AppHandle,
+) -> Result<Option<export::ExportSummary>, String> {
+    use tauri_plugin_dialog::DialogExt;
+
+    let format = format.unwrap_or_default();
+    let mut dialog = app_handle
+        .dialog()
+        .file()
+        .set_title("Export History")
+        .set_file_name(export::default_file_name(format))
+        .add_filter(format.extension().to_uppercase(), &[format.extension()]);
+    if let Ok(dir) = default_export_dir(&app_handle) {
+        dialog = dialog.set_directory(dir);
+    }
+    let Some(path) = file_dialog(dialog, true).await? else {
+        return Ok(None);
+    };
+
+    let store = history_store(&app_handle)?;
+    let count = export::export_to_file(&store, &path, format, &filter.unwrap_or_default())
+        .map_err(|e| e.to_string())?;
+    Ok(Some(export::ExportSummary { path: path.display().to_string(), count }))
+}
+
+/// Import an NDJSON file chosen in the native open dialog, attributing messages to
+/// `server` (default: configured instance); None when cancelled
+#[tauri::command]
+async fn import_history(
+    server: Option<String>,
+    app_handle: tauri::AppHandle,
+) -> Result<Option<export::ImportSummary>, String> {
+    use tauri_plugin_dialog::DialogExt;
+
+    let dialog = app_handle
+        .dialog()
+        .file()
+        .set_title("Import History")
+        .add_filter("NDJSON", &["ndjson", "jsonl", "json"]);
+    let Some(path) = file_dialog(dialog, false).await? else {
+        return Ok(None);
+    };
+
+    let server = match server {
+        Some(server) => server.trim_end_matches('/').to_string(),
+        None => match app_handle.try_state::<SharedConfig>() {
+            Some(shared) => shared.0.lock().await.api_base_url(),
+            None => load_config_sync(&app_handle).api_base_url(),
+        },
+    };
+    let store = history_store(&app_handle)?;
+    export::import_file(&store, &path, &server).map(Some).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn clear_history(app_handle: tauri::AppHandle) -> Result<usize, String> {
+    history_store(&app_handle)?.clear().map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 async fn navigate_to(url: String, app_handle: tauri::AppHandle) -> Result<(), String> {
     let window = app_handle
@@ -285,6 +687,156 @@ async fn navigate_to(url: String, app_handle: tauri::AppHandle) -> Result<(), St
     Ok(())
 }
 
+/// Show the main window and navigate it to a web UI page (e.g. a topic from the notification center)
+#[tauri::command]
+async fn open_in_web_ui(url: String, app_handle: tauri::AppHandle) -> Result<(), String> {
+    navigate_to(url, app_handle.clone()).await?;
+    if let Some(window) = app_handle.get_webview_window("main") {
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+    Ok(())
+}
+
+/// The click URL waiting for confirmation (only readable by the confirm-link window)
+#[tauri::command]
+fn pending_link(window: tauri::WebviewWindow, app_handle: tauri::AppHandle) -> Option<PendingLink> {
+    if window.label() != CONFIRM_LINK_WINDOW {
+        return None;
+    }
+    app_handle.try_state::<PendingLinkState>()?.0.lock().ok()?.clone()
+}
+
+/// Open alarms (only readable by the alarm window)
+#[tauri::command]
+async fn active_alarms(window: tauri::WebviewWindow, app_handle: tauri::AppHandle) -> Option<AlarmWindowState> {
+    if window.label() != ALARM_WINDOW {
+        return None;
+    }
+    let snooze_minutes = match app_handle.try_state::<SharedConfig>() {
+        Some(shared) => shared.0.lock().await.alarm.snooze_minutes,
+        None => 0,
+    };
+    let alarms = app_handle.try_state::<alarm::AlarmState>()?.alarms();
+    Some(AlarmWindowState { alarms, snooze_minutes })
+}
+
+/// Acknowledge one alarm, or all of them without an id
+#[tauri::command]
+fn acknowledge_alarm(id: Option<String>, window: tauri::WebviewWindow, app_handle: tauri::AppHandle) -> Result<(), String> {
+    if window.label() != ALARM_WINDOW {
+        return Err("Not allowed from this window".to_string());
+    }
+    let alarms = app_handle.try_state::<alarm::AlarmState>().ok_or("Alarms are not available")?;
+    if alarms.acknowledge(id.as_deref()) {
+        println!("Alarm acknowledged: {}", id.as_deref().unwrap_or("all"));
+    }
+    close_alarm_window_if_quiet(&app_handle);
+    Ok(())
+}
+
+/// Silence an alarm for the configured snooze, after which it rings again
+#[tauri::command]
+async fn snooze_alarm(id: String, window: tauri::WebviewWindow, app_handle: tauri::AppHandle) -> Result<(), String> {
+    if window.label() != ALARM_WINDOW {
+        return Err("Not allowed from this window".to_string());
+    }
+    let snooze_minutes = match app_handle.try_state::<SharedConfig>() {
+        Some(shared) => shared.0.lock().await.alarm.snooze_minutes,
+        None => 0,
+    };
+    if snooze_minutes == 0 {
+        return Err("Snoozing is disabled".to_string());
+    }
+    let alarms = app_handle.try_state::<alarm::AlarmState>().ok_or("Alarms are not available")?;
+    let until = chrono::Utc::now().timestamp().max(0) as u64 + snooze_minutes * 60;
+    if !alarms.snooze(&id, until) {
+        return Err("Alarm not found".to_string());
+    }
+    close_alarm_window_if_quiet(&app_handle);
+    Ok(())
+}
+
+/// Open (or drop) the pending click URL; `trust` opens future links of the topic without asking
+#[tauri::command]
+async fn resolve_pending_link(
+    open: bool,
+    trust: bool,
+    window: tauri::WebviewWindow,
+    app_handle: tauri::AppHandle,
+) -> Result<(), String> {
+    if window.label() != CONFIRM_LINK_WINDOW {
+        return Err("Not allowed from this window".to_string());
+    }
+    let link = app_handle
+        .try_state::<PendingLinkState>()
+        .and_then(|state| state.0.lock().ok()?.take());
+    let _ = window.close();
+    let Some(link) = link else { return Ok(()) };
+
+    if open && trust {
+        if let Some(shared) = app_handle.try_state::<SharedConfig>() {
+            let config = {
+                let mut config = shared.0.lock().await;
+                if let Some(sub) = config.topics.iter_mut().find(|s| s.topic == link.topic) {
+                    sub.trusted = true;
+                }
+                config.clone()
+            };
+            config::save_config(&app_handle, config).await.map_err(|e| e.to_string())?;
+        }
+    }
+    if open && actions::is_openable_url(&link.url) {
+        open::that(&link.url).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Open a URL from a notification, asking first for untrusted topics; only http(s) links are opened
+fn open_notification_link(app: &tauri::AppHandle, config: &config::AppConfig, url: String, topic: &str) -> Result<(), String> {
+    if !actions::is_openable_url(&url) {
+        return Err(format!("Refusing to open non-http(s) link '{}'", url));
+    }
+    if config.topic_settings(topic).trusted {
+        return open::that(&url).map_err(|e| e.to_string());
+    }
+    if let Some(state) = app.try_state::<PendingLinkState>() {
+        *state.0.lock().unwrap_or_else(|e| e.into_inner()) = Some(PendingLink { url, topic: topic.to_string() });
+    }
+    open_confirm_link_window(app).map_err(|e| e.to_string())
+}
+
+/// Route a notification click: run the pressed action button, open the click URL
+/// or show the topic in the main window
+async fn handle_notification_activation(app: &tauri::AppHandle, activation: notifications::NotificationActivation) {
+    let config = match app.try_state::<SharedConfig>() {
+        Some(shared) => shared.0.lock().await.clone(),
+        None => config::AppConfig::default(),
+    };
+    let data = activation.data;
+
+    let result = match activation.action.and_then(|i| data.actions.get(i)) {
+        Some(action) => match action.action.as_str() {
+            "view" => open_notification_link(app, &config, action.url.clone().unwrap_or_default(), &data.topic),
+            "http" => actions::send_http(action).await.map(|_| ()).map_err(|e| e.to_string()),
+            "broadcast" => app
+                .emit(actions::BROADCAST_EVENT, actions::BroadcastPayload::new(&data.topic, action))
+                .map_err(|e| e.to_string()),
+            other => Err(format!("Unsupported action '{}'", other)),
+        },
+        None => match data.click {
+            Some(url) => open_notification_link(app, &config, url, &data.topic),
+            None => {
+                let server = data.server.unwrap_or_else(|| config.api_base_url());
+                open_in_web_ui(notification_center::web_url(&server, &data.topic), app.clone()).await
+            }
+        },
+    };
+    if let Err(e) = result {
+        eprintln!("Failed to handle notification click: {}", e);
+    }
+}
+
 #[tauri::command]
 async fn complete_welcome(app_handle: tauri::AppHandle) -> Result<(), String> {
     let mut config = config::load_config(&app_handle)
@@ -396,6 +948,7 @@ fn main() -> anyhow::Result<()> {
 
     let app = tauri::Builder::default()
         .plugin(tauri_plugin_shell::init())
+        .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_single_instance::init(|app, argv, cwd| {
             println!("Another instance tried to run with args: {:?}, cwd: {:?}", argv, cwd);
             
@@ -430,6 +983,30 @@ fn main() -> anyhow::Result<()> {
             get_memory_usage,
             get_performance_metrics,
             test_ntfy_connection,
+            publish_message,
+            is_topic_encrypted,
+            set_topic_encryption,
+            list_history,
+            mark_history_read,
+            delete_history,
+            clear_history,
+            search_messages,
+            dry_run_rules,
+            hook_log,
+            set_webhook_headers,
+            set_smtp_credentials,
+            send_test_email,
+            list_notification_center,
+            mark_all_history_read,
+            open_notification_center,
+            open_in_web_ui,
+            pending_link,
+            resolve_pending_link,
+            active_alarms,
+            acknowledge_alarm,
+            snooze_alarm,
+            export_history,
+            import_history,
             navigate_to,
             complete_welcome,
             close_window,
@@ -452,7 +1029,28 @@ fn main() -> anyhow::Result<()> {
             let shared_config = Arc::new(Mutex::new(config.clone()));
             app.manage(SharedConfig(shared_config.clone()));
             let is_polling = Arc::new(AtomicBool::new(false));
+
+            // Local message history lives in the app data dir, independent of the webview cache
+            match app.path().app_data_dir() {
+                Ok(dir) => match history::HistoryStore::open(&dir.join("history.db")) {
+                    Ok(store) => {
+                        if let Err(e) = store.apply_retention(config.history_max_messages, config.history_retention_days) {
+                            eprintln!("Failed to apply history retention: {}", e);
+                        }
+                        app.manage(store);
+                    }
+                    Err(e) => eprintln!("Message history disabled: {}", e),
+                },
+                Err(e) => eprintln!("Failed to get app data directory for history: {}", e),
+            }
+            app.manage(hooks::HookRunner::new(&config.hooks));
             let badge_count = Arc::new(AtomicU32::new(0));
+            app.manage(UnreadBadge(badge_count.clone()));
+            app.manage(TrayFlash::default());
+            app.manage(PendingLinkState::default());
+            app.manage(alarm::AlarmState::load(
+                app.path().app_data_dir().ok().map(|dir| dir.join("alarms.json")),
+            ));
 
             // ── Create Main Window ──────────────────────────────────────
 
@@ -479,6 +1077,11 @@ fn main() -> anyhow::Result<()> {
                     //
                     // We also report Notification.permission as "granted" so the ntfy web UI
                     // never shows a "Notifications are blocked" banner.
+                    //
+                    // Publishing on encrypted topics is intercepted too: the web UI sends with
+                    // fetch (JSON body) or XMLHttpRequest (query parameters or X- headers), and
+                    // those requests go to the Rust `publish_message` command instead, which
+                    // encrypts title and body. Other topics publish unchanged.
                     r#"
                     (function() {
                         // Find the best available Tauri IPC invoke function
@@ -492,7 +1095,7 @@ fn main() -> anyhow::Result<()> {
                             return null;
                         }
 
-                        function sendNativeNotification(title, body, iconUrl) {
+                        function sendNativeNotification(title, body, iconUrl, topic) {
                             var invoke = getInvoke();
                             if (invoke) {
                                 var args = {
@@ -502,6 +1105,10 @@ fn main() -> anyhow::Result<()> {
                                 if (iconUrl) {
                                     args.icon_url = iconUrl;
                                 }
+                                // Lets Rust skip topics it already delivers from its own polling
+                                if (topic) {
+                                    args.topic = topic;
+                                }
                                 invoke('show_notification', args).catch(function(err) {
                                     console.error('ntfy.desktop: Failed to show native notification:', err);
                                 });
@@ -515,10 +1122,12 @@ fn main() -> anyhow::Result<()> {
                             this.title = title;
                             this.body = (options && options.body) || "";
                             var icon = "";
-                            if (options && options.data && options.data.message && options.data.message.icon) {
-                                icon = options.data.message.icon;
+                            var topic = "";
+                            if (options && options.data && options.data.message) {
+                                icon = options.data.message.icon || "";
+                                topic = options.data.message.topic || "";
                             }
-                            sendNativeNotification(title, this.body, icon);
+                            sendNativeNotification(title, this.body, icon, topic);
                         }
                         NativeNotification.permission = "granted";
                         NativeNotification.requestPermission = function(cb) {
@@ -538,10 +1147,12 @@ fn main() -> anyhow::Result<()> {
                                 // The message icon URL is in options.data.message.icon, not options.icon
                                 // options.icon is always the static ntfy logo
                                 var icon = "";
-                                if (options && options.data && options.data.message && options.data.message.icon) {
-                                    icon = options.data.message.icon;
+                                var topic = "";
+                                if (options && options.data && options.data.message) {
+                                    icon = options.data.message.icon || "";
+                                    topic = options.data.message.topic || "";
                                 }
-                                sendNativeNotification(title, (options && options.body) || "", icon);
+                                sendNativeNotification(title, (options && options.body) || "", icon, topic);
                                 return Promise.resolve();
                             };
                         }
@@ -553,6 +1164,137 @@ fn main() -> anyhow::Result<()> {
                             };
                         }
 
+                        // ── Publishing on encrypted topics ──
+
+                        var PRIORITIES = { min: 1, low: 2, default: 3, high: 4, max: 5, urgent: 5 };
+
+                        function isEncryptedTopic(topic) {
+                            var invoke = getInvoke();
+                            if (!invoke || !topic) {
+                                return Promise.resolve(false);
+                            }
+                            return invoke('is_topic_encrypted', { topic: topic }).catch(function() {
+                                return false;
+                            });
+                        }
+
+                        function firstOf(lookup, names) {
+                            for (var i = 0; i < names.length; i++) {
+                                var value = lookup(names[i]);
+                                if (value) {
+                                    return String(value);
+                                }
+                            }
+                            return null;
+                        }
+
+                        function publishEncrypted(topic, message, title, priority, tags) {
+                            var args = { topic: topic, message: message || '' };
+                            if (title) {
+                                args.title = title;
+                            }
+                            if (priority) {
+                                args.priority = PRIORITIES[String(priority).toLowerCase()] || parseInt(priority, 10) || 3;
+                            }
+                            if (tags) {
+                                args.tags = (Array.isArray(tags) ? tags : String(tags).split(','))
+                                    .map(function(t) { return String(t).trim(); })
+                                    .filter(function(t) { return t; });
+                            }
+                            return getInvoke()('publish_message', args);
+                        }
+
+                        // ntfy-style response bodies, so the web UI shows success or the error
+                        function publishResult(topic, error) {
+                            return error
+                                ? { status: 400, body: JSON.stringify({ code: 40000, http: 400, error: String(error) }) }
+                                : { status: 200, body: JSON.stringify({ event: 'message', topic: topic, time: Math.floor(Date.now() / 1000) }) };
+                        }
+
+                        // JSON publish: PUT/POST to the server root with the topic in the body
+                        var originalFetch = window.fetch;
+                        window.fetch = function(input, init) {
+                            var args = arguments;
+                            var method = ((init && init.method) || (input && input.method) || 'GET').toUpperCase();
+                            var url = new URL(typeof input === 'string' ? input : (input && input.url) || String(input), location.href);
+                            var body = null;
+                            if ((method === 'PUT' || method === 'POST') && url.origin === location.origin
+                                && url.pathname.replace(/\/+$/, '') === '' && init && typeof init.body === 'string') {
+                                try {
+                                    body = JSON.parse(init.body);
+                                } catch (e) {
+                                    body = null;
+                                }
+                            }
+                            if (!body || typeof body.topic !== 'string') {
+                                return originalFetch.apply(window, args);
+                            }
+                            return isEncryptedTopic(body.topic).then(function(encrypted) {
+                                if (!encrypted) {
+                                    return originalFetch.apply(window, args);
+                                }
+                                return publishEncrypted(body.topic, body.message, body.title, body.priority, body.tags)
+                                    .then(function() { return publishResult(body.topic, null); },
+                                          function(err) { return publishResult(body.topic, err); })
+                                    .then(function(result) {
+                                        return new Response(result.body, {
+                                            status: result.status,
+                                            headers: { 'Content-Type': 'application/json' }
+                                        });
+                                    });
+                            });
+                        };
+
+                        // Publish dialog: PUT/POST to /{topic} with the message as the body
+                        var originalOpen = XMLHttpRequest.prototype.open;
+                        var originalSetRequestHeader = XMLHttpRequest.prototype.setRequestHeader;
+                        var originalSend = XMLHttpRequest.prototype.send;
+                        XMLHttpRequest.prototype.open = function(method, url) {
+                            this.__ntfyPublish = { method: String(method).toUpperCase(), url: new URL(String(url), location.href), headers: {} };
+                            return originalOpen.apply(this, arguments);
+                        };
+                        XMLHttpRequest.prototype.setRequestHeader = function(name, value) {
+                            if (this.__ntfyPublish) {
+                                this.__ntfyPublish.headers[String(name).toLowerCase()] = value;
+                            }
+                            return originalSetRequestHeader.apply(this, arguments);
+                        };
+                        XMLHttpRequest.prototype.send = function(body) {
+                            var xhr = this;
+                            var args = arguments;
+                            var request = xhr.__ntfyPublish;
+                            var segments = request ? request.url.pathname.split('/').filter(function(s) { return s; }) : [];
+                            if (!request || (request.method !== 'PUT' && request.method !== 'POST')
+                                || request.url.origin !== location.origin || segments.length !== 1) {
+                                return originalSend.apply(xhr, args);
+                            }
+                            var topic = decodeURIComponent(segments[0]);
+                            isEncryptedTopic(topic).then(function(encrypted) {
+                                if (!encrypted) {
+                                    return originalSend.apply(xhr, args);
+                                }
+                                var lookup = function(name) {
+                                    return request.url.searchParams.get(name) || request.headers[name] || request.headers['x-' + name];
+                                };
+                                var sent = typeof body === 'string'
+                                    ? publishEncrypted(topic, body, firstOf(lookup, ['title', 't']),
+                                        firstOf(lookup, ['priority', 'prio', 'p']), firstOf(lookup, ['tags', 'tag', 'ta']))
+                                    : Promise.reject('Attachments cannot be sent to encrypted topics');
+                                return sent
+                                    .then(function() { return publishResult(topic, null); },
+                                          function(err) { return publishResult(topic, err); })
+                                    .then(function(result) {
+                                        var fields = { readyState: 4, status: result.status, responseText: result.body, response: result.body };
+                                        Object.keys(fields).forEach(function(key) {
+                                            Object.defineProperty(xhr, key, { value: fields[key], configurable: true });
+                                        });
+                                        ['readystatechange', 'load', 'loadend'].forEach(function(type) {
+                                            xhr.dispatchEvent(new Event(type));
+                                        });
+                                    });
+                            });
+                        };
+
                         console.log("ntfy.desktop: Notification API bridged to native toasts");
                     })();
                     "#,
@@ -600,33 +1342,34 @@ fn main() -> anyhow::Result<()> {
             let window_for_close = window.clone();
             let quit_flag = Arc::new(AtomicBool::new(false));
             let quit_flag_close = quit_flag.clone();
+            let app_handle_focus = app_handle.clone();
 
-            window.on_window_event(move |event| {
-                if let WindowEvent::CloseRequested { api, .. } = event {
-                    if !quit_on_close && !quit_flag_close.load(Ordering::SeqCst) {
-                        api.prevent_close();
-                        let _ = window_for_close.hide();
-                    }
+            window.on_window_event(move |event| match event {
+                WindowEvent::CloseRequested { api, .. }
+                    if !quit_on_close && !quit_flag_close.load(Ordering::SeqCst) =>
+                {
+                    api.prevent_close();
+                    let _ = window_for_close.hide();
                 }
+                // Viewing the web UI counts as having seen the new messages
+                WindowEvent::Focused(true) => reset_badge(&app_handle_focus),
+                _ => {}
             });
 
             // ── System Tray ─────────────────────────────────────────────
 
-            let show_item = MenuItemBuilder::with_id("show", "Show App").build(app)?;
-            let quit_item = MenuItemBuilder::with_id("quit", "Quit").build(app)?;
-            let tray_menu = MenuBuilder::new(app)
-                .item(&show_item)
-                .separator()
-                .item(&quit_item)
-                .build()?;
+            // Rebuilt from the polling state whenever it changes (see refresh_tray_menu)
+            let polling_state = polling_state::SharedPollingState::default();
+            polling_state.lock().paused_until = config.pause.as_ref().map(|p| p.until);
+            app.manage(polling_state.clone());
+            let tray_menu = build_tray_menu(&app_handle, &polling_state.lock(), &config)?;
 
             let icon = load_tray_icon();
 
             let window_for_tray = window.clone();
             let quit_flag_tray = quit_flag.clone();
-            let badge_count_tray = badge_count.clone();
 
-            let _tray = TrayIconBuilder::new()
+            let _tray = TrayIconBuilder::with_id(TRAY_ID)
                 .icon(icon)
                 .menu(&tray_menu)
                 .tooltip("ntfy.desktop")
@@ -642,7 +1385,6 @@ fn main() -> anyhow::Result<()> {
                         } else {
                             let _ = window_for_tray.show();
                             let _ = window_for_tray.set_focus();
-                            badge_count_tray.store(0, Ordering::SeqCst);
                         }
                     }
                 })
@@ -657,15 +1399,23 @@ fn main() -> anyhow::Result<()> {
                                 let _ = win.set_focus();
                             }
                         }
+                        "notifications" => {
+                            let _ = open_notification_center_window(app);
+                        }
                         "quit" => {
                             quit_flag_tray.store(true, Ordering::SeqCst);
                             app.exit(0);
                         }
-                        _ => {}
+                        id => handle_tray_menu_event(app, id),
                     },
                 )
                 .build(app)?;
 
+            let app_handle_tray = app_handle.clone();
+            app.listen("polling-state-changed", move |_| {
+                refresh_tray_menu(&app_handle_tray);
+            });
+
             // ── Application Menu Bar ────────────────────────────────────────
 
             let app_menu = create_app_menu(&app_handle)?;
@@ -697,14 +1447,83 @@ fn main() -> anyhow::Result<()> {
                     .await;
             });
 
+            // ── Alarms ──────────────────────────────────────────────────
+
+            let app_handle_alarm = app_handle.clone();
+            app.listen("alarm-raised", move |_| {
+                if let Err(e) = open_alarm_window(&app_handle_alarm) {
+                    eprintln!("Failed to open alarm window: {}", e);
+                }
+            });
+            // Pages left open before a restart
+            let has_open_alarms = app
+                .try_state::<alarm::AlarmState>()
+                .map_or(false, |alarms| !alarms.alarms().is_empty());
+            if has_open_alarms {
+                if let Err(e) = open_alarm_window(&app_handle) {
+                    eprintln!("Failed to open alarm window: {}", e);
+                }
+            }
+            let app_handle_ring = app_handle.clone();
+            tauri::async_runtime::spawn(async move {
+                let mut interval = tokio::time::interval(std::time::Duration::from_secs(ALARM_TICK_SECS));
+                loop {
+                    interval.tick().await;
+                    ring_alarms(&app_handle_ring).await;
+                }
+            });
+
+            // ── Notification clicks ─────────────────────────────────────
+
+            let app_handle_click = app_handle.clone();
+            let mut activations = notifications::subscribe_activations();
+            tauri::async_runtime::spawn(async move {
+                loop {
+                    match activations.recv().await {
+                        Ok(activation) => handle_notification_activation(&app_handle_click, activation).await,
+                        Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => continue,
+                        Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+                    }
+                }
+            });
+
+            // ── UnifiedPush Distributor (Linux) ─────────────────────────
+
+            #[cfg(target_os = "linux")]
+            if config.unifiedpush_enabled {
+                let app_handle_push = app_handle.clone();
+                let config_for_push = shared_config.clone();
+                tauri::async_runtime::spawn(async move {
+                    let registry_path = match app_handle_push.path().app_data_dir() {
+                        Ok(dir) => dir.join("unifiedpush.json"),
+                        Err(e) => {
+                            eprintln!("Failed to get app data directory for UnifiedPush: {}", e);
+                            return;
+                        }
+                    };
+                    match unifiedpush::start(&registry_path, config_for_push).await {
+                        Ok(state) => {
+                            app_handle_push.manage(state);
+                        }
+                        Err(e) => eprintln!("Failed to start UnifiedPush distributor: {}", e),
+                    }
+                });
+            }
+
             // ── Badge count listener ─────────────────────────────────────
 
+            let app_handle_badge = app_handle.clone();
             app.listen("badge-update", move |event| {
                 if let Ok(count) = serde_json::from_str::<u32>(event.payload()) {
-                    badge_count.fetch_add(count, Ordering::SeqCst);
+                    let total = badge_count.fetch_add(count, Ordering::SeqCst) + count;
+                    update_badge(&app_handle_badge, total);
                 }
             });
 
+            // Priority policies that flash the tray (see priority.rs)
+            let app_handle_flash = app_handle.clone();
+            app.listen("tray-flash", move |_| flash_tray(&app_handle_flash));
+
             Ok(())
         })
         .build(tauri::generate_context!())
@@ -756,6 +1575,8 @@ fn load_config_sync(app_handle: &tauri::AppHandle) -> config::AppConfig {
             }
         }
     };
+    // As in config::load_config: later saves would otherwise drop legacy settings
+    config.migrate();
 
     // Merge credentials from OS keychain
     match credentials::load_credentials() {
@@ -798,8 +1619,25 @@ fn create_app_menu(app: &tauri::AppHandle) -> Result<tauri::menu::Menu<tauri::Wr
         })
         .build(app)?;
 
+    let export_history_item =
+        MenuItemBuilder::with_id("export-history", "Export History…").build(app)?;
+
+    let import_history_item =
+        MenuItemBuilder::with_id("import-history", "Import History…").build(app)?;
+
+    let notifications_item = MenuItemBuilder::with_id("notifications", "Notifications")
+        .accelerator(if cfg!(target_os = "macos") {
+            "Cmd+Shift+N"
+        } else {
+            "Ctrl+Shift+N"
+        })
+        .build(app)?;
+
     let file_menu = SubmenuBuilder::new(app, "File")
+        .item(&notifications_item)
         .item(&test_notification_item)
+        .item(&export_history_item)
+        .item(&import_history_item)
         .separator()
         .item(&quit_item)
         .build()?;
@@ -871,7 +1709,7 @@ fn create_app_menu(app: &tauri::AppHandle) -> Result<tauri::menu::Menu<tauri::Wr
 
 /// Open settings window (unified single page)
 fn open_settings_window(app: &tauri::AppHandle) -> Result<(), tauri::Error> {
-    let window_label = "settings";
+    let window_label = SETTINGS_WINDOW;
 
     // Check if window already exists
     if let Some(existing_window) = app.get_webview_window(window_label) {
@@ -893,6 +1731,139 @@ fn open_settings_window(app: &tauri::AppHandle) -> Result<(), tauri::Error> {
     Ok(())
 }
 
+/// Ask before opening a click URL from an untrusted topic
+fn open_confirm_link_window(app: &tauri::AppHandle) -> Result<(), tauri::Error> {
+    if let Some(existing_window) = app.get_webview_window(CONFIRM_LINK_WINDOW) {
+        // Show the newest link
+        let _ = existing_window.eval("window.location.reload()");
+        let _ = existing_window.show();
+        let _ = existing_window.set_focus();
+        return Ok(());
+    }
+
+    tauri::WebviewWindowBuilder::new(
+        app,
+        CONFIRM_LINK_WINDOW,
+        tauri::WebviewUrl::App("/confirm-link.html".into()),
+    )
+    .title("Open Link?")
+    .inner_size(440.0, 240.0)
+    .resizable(false)
+    .always_on_top(true)
+    .build()?;
+
+    Ok(())
+}
+
+/// Open the alarm window, or refresh it when it is already open
+fn open_alarm_window(app: &tauri::AppHandle) -> Result<(), tauri::Error> {
+    if let Some(existing_window) = app.get_webview_window(ALARM_WINDOW) {
+        let _ = existing_window.emit("alarms-changed", ());
+        let _ = existing_window.show();
+        let _ = existing_window.set_focus();
+        return Ok(());
+    }
+
+    tauri::WebviewWindowBuilder::new(app, ALARM_WINDOW, tauri::WebviewUrl::App("/alarm.html".into()))
+        .title("Alarm")
+        .inner_size(460.0, 320.0)
+        .resizable(true)
+        .always_on_top(true)
+        .build()?;
+
+    Ok(())
+}
+
+/// Close the alarm window once nothing is ringing, otherwise refresh it
+fn close_alarm_window_if_quiet(app: &tauri::AppHandle) {
+    let now = chrono::Utc::now().timestamp().max(0) as u64;
+    let ringing = app
+        .try_state::<alarm::AlarmState>()
+        .map_or(false, |alarms| alarms.alarms().iter().any(|a| !a.is_snoozed(now)));
+    if let Some(window) = app.get_webview_window(ALARM_WINDOW) {
+        if ringing {
+            let _ = window.emit("alarms-changed", ());
+        } else {
+            let _ = window.close();
+        }
+    }
+}
+
+/// Repeat due alarms, bring back snoozed ones and keep the tray flashing while any rings
+async fn ring_alarms(app: &tauri::AppHandle) {
+    let Some(alarms) = app.try_state::<alarm::AlarmState>() else { return };
+    let repeat_secs = match app.try_state::<SharedConfig>() {
+        Some(shared) => shared.0.lock().await.alarm.repeat_secs,
+        None => alarm::AlarmSettings::default().repeat_secs,
+    };
+    let tick = alarms.tick(chrono::Utc::now().timestamp().max(0) as u64, repeat_secs);
+
+    for alarm in tick.ring {
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = NotificationManager::new().show_notification_full(&alarm.notification()).await {
+                eprintln!("Failed to repeat alarm: {}", e);
+            }
+        });
+    }
+    if tick.active {
+        flash_tray(app);
+    }
+    if tick.woke {
+        if let Err(e) = open_alarm_window(app) {
+            eprintln!("Failed to open alarm window: {}", e);
+        }
+    }
+}
+
+/// Open the notification center window (reads local history, not the web UI)
+fn open_notification_center_window(app: &tauri::AppHandle) -> Result<(), tauri::Error> {
+    let window_label = "notifications";
+
+    if let Some(existing_window) = app.get_webview_window(window_label) {
+        let _ = existing_window.show();
+        let _ = existing_window.set_focus();
+        return Ok(());
+    }
+
+    let window = tauri::WebviewWindowBuilder::new(
+        app,
+        window_label,
+        tauri::WebviewUrl::App("/notifications.html".into()),
+    )
+    .title("Notifications")
+    .inner_size(480.0, 720.0)
+    .resizable(true)
+    .build()?;
+
+    let app_handle = app.clone();
+    window.on_window_event(move |event| {
+        if let WindowEvent::Focused(true) = event {
+            reset_badge(&app_handle);
+        }
+    });
+
+    Ok(())
+}
+
+/// Open the export window, where topic, time range and format are chosen before saving
+fn open_export_window(app: &tauri::AppHandle) -> Result<(), tauri::Error> {
+    let window_label = "export";
+
+    if let Some(existing_window) = app.get_webview_window(window_label) {
+        let _ = existing_window.show();
+        let _ = existing_window.set_focus();
+        return Ok(());
+    }
+
+    tauri::WebviewWindowBuilder::new(app, window_label, tauri::WebviewUrl::App("/export.html".into()))
+        .title("Export History")
+        .inner_size(420.0, 420.0)
+        .resizable(false)
+        .build()?;
+
+    Ok(())
+}
+
 /// Handle menu events
 fn handle_menu_event(app: &tauri::AppHandle, event: tauri::menu::MenuEvent) {
     match event.id().as_ref() {
@@ -913,6 +1884,46 @@ fn handle_menu_event(app: &tauri::AppHandle, event: tauri::menu::MenuEvent) {
                 }
             });
         }
+        "notifications" => {
+            let _ = open_notification_center_window(app);
+        }
+        "export-history" => {
+            println!("Menu: Export history clicked");
+            if let Err(e) = open_export_window(app) {
+                eprintln!("Failed to open export window: {}", e);
+            }
+        }
+        "import-history" => {
+            println!("Menu: Import history clicked");
+            let app_handle = app.clone();
+            tauri::async_runtime::spawn(async move {
+                let (title, body) = match import_history(None, app_handle).await {
+                    Ok(Some(summary)) => (
+                        "History Imported",
+                        format!(
+                            "Imported {} messages ({} already known, {} invalid lines)",
+                            summary.imported, summary.skipped, summary.invalid
+                        ),
+                    ),
+                    Ok(None) => return,
+                    Err(e) => {
+                        eprintln!("Failed to import history from menu: {}", e);
+                        ("History Import Failed", e)
+                    }
+                };
+                if let Err(e) = crate::notifications::show_notification(
+                    title,
+                    &body,
+                    false,
+                    &crate::config::NotificationSound::Default,
+                    false,
+                )
+                .await
+                {
+                    eprintln!("Failed to show import notification: {}", e);
+                }
+            });
+        }
         "quit" => {
             app.exit(0);
         }
@@ -949,6 +1960,272 @@ fn handle_menu_event(app: &tauri::AppHandle, event: tauri::menu::MenuEvent) {
     }
 }
 
+/// Build the tray menu from the polling state: status, recent messages, mute and pause controls
+fn build_tray_menu(
+    app: &tauri::AppHandle,
+    state: &polling_state::PollingState,
+    config: &config::AppConfig,
+) -> Result<tauri::menu::Menu<tauri::Wry>, tauri::Error> {
+    use tauri::menu::{CheckMenuItemBuilder, SubmenuBuilder};
+
+    let mut menu = MenuBuilder::new(app).item(
+        &MenuItemBuilder::with_id("status", state.connection.label())
+            .enabled(false)
+            .build(app)?,
+    );
+    if let Some(until) = state.paused_until {
+        let until = chrono::DateTime::from_timestamp(until as i64, 0)
+            .map(|dt| dt.with_timezone(&chrono::Local).format("%a %H:%M").to_string())
+            .unwrap_or_default();
+        menu = menu.item(
+            &MenuItemBuilder::with_id("paused", format!("Paused until {}", until))
+                .enabled(false)
+                .build(app)?,
+        );
+    }
+
+    // ── Recent messages ─────────────────────────────────────────────
+    menu = menu.separator();
+    if state.recent.is_empty() {
+        menu = menu.item(
+            &MenuItemBuilder::with_id("recent-none", "No recent messages")
+                .enabled(false)
+                .build(app)?,
+        );
+    }
+    // Keyed by message rather than position: the list changes with every poll
+    for recent in &state.recent {
+        let id = format!("recent:{}:{}", recent.id, recent.server);
+        menu = menu.item(&MenuItemBuilder::with_id(id, &recent.label).build(app)?);
+    }
+
+    // ── Topics, pause, mark read ─────────────────────────────────────
+    let mut topics_menu = SubmenuBuilder::new(app, "Mute Topics");
+    if state.topics.is_empty() {
+        topics_menu = topics_menu.item(
+            &MenuItemBuilder::with_id("topics-none", "No topics subscribed")
+                .enabled(false)
+                .build(app)?,
+        );
+    }
+    for topic in &state.topics {
+        let settings = config.topic_settings(topic);
+        topics_menu = topics_menu.item(
+            &CheckMenuItemBuilder::with_id(format!("mute:{}", topic), &settings.display_name)
+                .checked(settings.muted)
+                .build(app)?,
+        );
+    }
+
+    let mut pause_menu = SubmenuBuilder::new(app, "Pause Notifications")
+        .item(&MenuItemBuilder::with_id("pause:30m", "For 30 Minutes").build(app)?)
+        .item(&MenuItemBuilder::with_id("pause:1h", "For 1 Hour").build(app)?)
+        .item(&MenuItemBuilder::with_id("pause:tomorrow", "Until Tomorrow").build(app)?);
+    if state.paused_until.is_some() {
+        pause_menu = pause_menu
+            .separator()
+            .item(&MenuItemBuilder::with_id("resume", "Resume Now").build(app)?);
+    }
+
+    menu.separator()
+        .item(&topics_menu.build()?)
+        .item(&pause_menu.build()?)
+        .item(&MenuItemBuilder::with_id("mark-all-read", "Mark All Read").build(app)?)
+        .separator()
+        .item(&MenuItemBuilder::with_id("show", "Show App").build(app)?)
+        .item(&MenuItemBuilder::with_id("notifications", "Notifications").build(app)?)
+        .separator()
+        .item(&MenuItemBuilder::with_id("quit", "Quit").build(app)?)
+        .build()
+}
+
+/// Rebuild the tray menu from the current polling state and mute settings
+fn refresh_tray_menu(app: &tauri::AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let config = match app.try_state::<SharedConfig>() {
+            Some(shared) => shared.0.lock().await.clone(),
+            None => config::AppConfig::default(),
+        };
+        let (Some(tray), Some(state)) = (app.tray_by_id(TRAY_ID), app.try_state::<polling_state::SharedPollingState>())
+        else {
+            return;
+        };
+        let snapshot = state.lock().clone();
+        match build_tray_menu(&app, &snapshot, &config) {
+            Ok(menu) => {
+                let _ = tray.set_menu(Some(menu));
+            }
+            Err(e) => eprintln!("Failed to rebuild tray menu: {}", e),
+        }
+    });
+}
+
+/// Handle tray menu items generated by build_tray_menu
+fn handle_tray_menu_event(app: &tauri::AppHandle, id: &str) {
+    let Some(state) = app.try_state::<polling_state::SharedPollingState>() else { return };
+
+    if let Some((message_id, server)) = id.strip_prefix("recent:").and_then(|key| key.split_once(':')) {
+        let recent = state.lock().recent.iter().find(|r| r.id == message_id && r.server == server).cloned();
+        let Some(recent) = recent else { return };
+        if let Some(store) = app.try_state::<history::HistoryStore>() {
+            let key = history::MessageKey { server: recent.server.clone(), id: recent.id.clone() };
+            let _ = store.mark_read(&[key], true);
+            let _ = app.emit("history-updated", 0);
+        }
+        let app = app.clone();
+        tauri::async_runtime::spawn(async move {
+            let result = match recent.click {
+                Some(click) => {
+                    let config = match app.try_state::<SharedConfig>() {
+                        Some(shared) => shared.0.lock().await.clone(),
+                        None => config::AppConfig::default(),
+                    };
+                    open_notification_link(&app, &config, click, &recent.topic)
+                }
+                None => open_in_web_ui(notification_center::web_url(&recent.server, &recent.topic), app).await,
+            };
+            if let Err(e) = result {
+                eprintln!("Failed to open recent message: {}", e);
+            }
+        });
+        return;
+    }
+
+    if let Some(topic) = id.strip_prefix("mute:") {
+        let topic = topic.to_string();
+        let app = app.clone();
+        tauri::async_runtime::spawn(async move {
+            let Some(shared) = app.try_state::<SharedConfig>() else { return };
+            let config = {
+                let mut config = shared.0.lock().await;
+                let muted = config.is_topic_muted(&topic);
+                config.set_topic_muted(&topic, !muted);
+                config.clone()
+            };
+            if let Err(e) = config::save_config(&app, config).await {
+                eprintln!("Failed to save muted topics: {}", e);
+            }
+            refresh_tray_menu(&app);
+        });
+        return;
+    }
+
+    let duration = match id {
+        "pause:30m" => Some(pause::PauseDuration::Minutes(30)),
+        "pause:1h" => Some(pause::PauseDuration::Minutes(60)),
+        "pause:tomorrow" => Some(pause::PauseDuration::UntilTomorrow),
+        _ => None,
+    };
+    match (id, duration) {
+        (_, Some(_)) | ("resume", _) => {
+            let Some(shared) = app.try_state::<SharedConfig>() else { return };
+            let config = shared.0.clone();
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                match duration {
+                    Some(duration) => pause::pause(&app, &config, duration.until(chrono::Local::now())).await,
+                    None => pause::resume(&app, &config).await,
+                }
+            });
+        }
+        ("mark-all-read", _) => {
+            if let Some(store) = app.try_state::<history::HistoryStore>() {
+                if let Err(e) = store.mark_all_read() {
+                    eprintln!("Failed to mark messages read: {}", e);
+                }
+                let _ = app.emit("history-updated", 0);
+            }
+            reset_badge(app);
+        }
+        _ => {}
+    }
+}
+
+/// Id of the system tray icon
+const TRAY_ID: &str = "main";
+
+/// Show the unread count on the tray icon and the taskbar / dock / launcher where supported
+fn update_badge(app: &tauri::AppHandle, count: u32) {
+    if let Some(tray) = app.tray_by_id(TRAY_ID) {
+        let icon = load_tray_icon();
+        let icon = match image::RgbaImage::from_raw(icon.width(), icon.height(), icon.rgba().to_vec()) {
+            Some(mut rgba) => {
+                badge::draw_badge(&mut rgba, count);
+                let (width, height) = rgba.dimensions();
+                Image::new_owned(rgba.into_raw(), width, height)
+            }
+            None => icon,
+        };
+        if let Err(e) = tray.set_icon(Some(icon)) {
+            eprintln!("Failed to update tray icon badge: {}", e);
+        }
+        let tooltip = match count {
+            0 => "ntfy.desktop".to_string(),
+            n => format!("ntfy.desktop – {} new", n),
+        };
+        let _ = tray.set_tooltip(Some(tooltip));
+    }
+
+    if let Some(window) = app.get_webview_window("main") {
+        // Windows has no badge count; an overlay icon is the taskbar equivalent
+        #[cfg(target_os = "windows")]
+        {
+            let overlay = badge::overlay_icon(count, 32).map(|rgba| {
+                let (width, height) = rgba.dimensions();
+                Image::new_owned(rgba.into_raw(), width, height)
+            });
+            let _ = window.set_overlay_icon(overlay);
+        }
+        #[cfg(not(target_os = "windows"))]
+        {
+            // Unsupported on some Linux desktops; the tray icon still shows the count
+            let _ = window.set_badge_count(if count == 0 { None } else { Some(count as i64) });
+        }
+    }
+}
+
+/// Clear the unread badge once the user has looked at the messages
+fn reset_badge(app: &tauri::AppHandle) {
+    if let Some(badge) = app.try_state::<UnreadBadge>() {
+        if badge.0.swap(0, Ordering::SeqCst) != 0 {
+            update_badge(app, 0);
+        }
+    }
+}
+
+/// Times the tray icon blinks for a flashing notification
+const TRAY_FLASH_BLINKS: u32 = 5;
+
+/// Blink the tray icon and ask the window manager for attention (taskbar flash on Windows)
+fn flash_tray(app: &tauri::AppHandle) {
+    let Some(flash) = app.try_state::<TrayFlash>() else { return };
+    let flashing = flash.0.clone();
+    if flashing.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.request_user_attention(Some(tauri::UserAttentionType::Informational));
+    }
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let icon = load_tray_icon();
+        let blank = Image::new_owned(vec![0u8; icon.rgba().len()], icon.width(), icon.height());
+        for blink in 0..TRAY_FLASH_BLINKS * 2 {
+            if let Some(tray) = app.tray_by_id(TRAY_ID) {
+                let frame = if blink % 2 == 0 { blank.clone() } else { icon.clone() };
+                let _ = tray.set_icon(Some(frame));
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+        }
+        // Back to the icon with the current unread count
+        let count = app.try_state::<UnreadBadge>().map_or(0, |badge| badge.0.load(Ordering::SeqCst));
+        update_badge(&app, count);
+        flashing.store(false, Ordering::SeqCst);
+    });
+}
+
 /// Load the tray icon from embedded bytes
 fn load_tray_icon() -> Image<'static> {
     let ico_bytes = include_bytes!("../icons/icon.ico");
@@ -0,0 +1,117 @@
+//! Data for the notification center window, built from local history only so
+//! the window works even when the instance's web UI is unreachable.
+
+use anyhow::Result;
+use serde::Serialize;
+
+use super::history::{HistoryEntry, HistoryFilter, HistoryStore};
+use super::ntfy::{NtfyAction, NtfyAttachment};
+
+/// A history entry with the parts of the raw message the window renders
+#[derive(Debug, Clone, Serialize)]
+pub struct CenterItem {
+    #[serde(flatten)]
+    pub entry: HistoryEntry,
+    pub click: Option<String>,
+    pub icon: Option<String>,
+    pub actions: Vec<NtfyAction>,
+    pub attachment: Option<NtfyAttachment>,
+    /// Topic page in the web UI of the server the message came from
+    pub web_url: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CenterPage {
+    pub total: u64,
+    pub unread: u64,
+    pub offset: u64,
+    pub items: Vec<CenterItem>,
+}
+
+/// Web UI URL of a topic: `{server}/{topic}`
+pub fn web_url(server: &str, topic: &str) -> String {
+    format!("{}/{}", server.trim_end_matches('/'), topic)
+}
+
+impl From<HistoryEntry> for CenterItem {
+    fn from(entry: HistoryEntry) -> Self {
+        // Entries always parse in practice; a bad raw payload just loses the extras
+        let message = entry.to_message().ok();
+        let (click, icon, actions, attachment) = match message {
+            Some(msg) => (msg.click, msg.icon, msg.actions.unwrap_or_default(), msg.attachment),
+            None => (None, None, Vec::new(), None),
+        };
+        CenterItem {
+            web_url: web_url(&entry.server, &entry.topic),
+            entry,
+            click: click.filter(|c| !c.is_empty()),
+            icon: icon.filter(|i| !i.is_empty()),
+            actions,
+            attachment,
+        }
+    }
+}
+
+/// One page of notification center items, newest first
+pub fn list(store: &HistoryStore, filter: &HistoryFilter, offset: u64, limit: u64) -> Result<CenterPage> {
+    let page = store.list(filter, offset, limit)?;
+    Ok(CenterPage {
+        total: page.total,
+        unread: store.unread_count()?,
+        offset: page.offset,
+        items: page.entries.into_iter().map(CenterItem::from).collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ntfy::NtfyMessage;
+
+    #[test]
+    fn test_items_carry_message_extras() {
+        let store = HistoryStore::open_in_memory().unwrap();
+        let msg: NtfyMessage = serde_json::from_value(serde_json::json!({
+            "id": "a1",
+            "time": 1700000000,
+            "event": "message",
+            "topic": "backups",
+            "message": "Nightly backup done",
+            "click": "https://backups.example.com/report",
+            "actions": [{ "action": "view", "label": "Open report", "url": "https://backups.example.com" }],
+            "attachment": {
+                "name": "report.pdf",
+                "type": "application/pdf",
+                "size": 4096,
+                "url": "https://ntfy.example.com/file/a1.pdf"
+            }
+        }))
+        .unwrap();
+        store.insert("https://ntfy.example.com/", &msg).unwrap();
+        store.insert("https://ntfy.sh", &serde_json::from_value(serde_json::json!({
+            "id": "b2", "time": 1700000100, "topic": "alerts", "message": "plain"
+        })).unwrap()).unwrap();
+        let b2 = crate::history::MessageKey { server: "https://ntfy.sh".to_string(), id: "b2".to_string() };
+        store.mark_read(&[b2], true).unwrap();
+
+        let page = list(&store, &HistoryFilter::default(), 0, 10).unwrap();
+        assert_eq!(page.total, 2);
+        assert_eq!(page.unread, 1);
+
+        let plain = &page.items[0];
+        assert_eq!(plain.web_url, "https://ntfy.sh/alerts");
+        assert!(plain.actions.is_empty() && plain.attachment.is_none() && plain.click.is_none());
+
+        let item = &page.items[1];
+        assert_eq!(item.web_url, "https://ntfy.example.com/backups");
+        assert_eq!(item.click.as_deref(), Some("https://backups.example.com/report"));
+        assert_eq!(item.actions[0].label, "Open report");
+        let attachment = item.attachment.as_ref().unwrap();
+        assert_eq!(attachment.name, "report.pdf");
+        assert_eq!(attachment.content_type.as_deref(), Some("application/pdf"));
+
+        let json = serde_json::to_value(item).unwrap();
+        assert_eq!(json["topic"], "backups");
+        assert_eq!(json["attachment"]["type"], "application/pdf");
+    }
+}
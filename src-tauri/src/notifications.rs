@@ -1,13 +1,15 @@
 use anyhow::Result;
 use crate::config::NotificationSound;
+use crate::ntfy::NtfyAction;
 use std::process::Command;
 use sha2::{Sha256, Digest};
+use tokio::sync::{broadcast, OnceCell};
 
 #[derive(Debug, Clone)]
 pub struct NotificationManager;
 
 /// Full notification data matching Electron app format
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct NotificationData {
     pub title: String,
     pub subtitle: Option<String>,
@@ -17,7 +19,46 @@ pub struct NotificationData {
     pub urgent: bool,
     pub sound: NotificationSound,
     pub persistent: bool,
+    /// Remote icon (e.g. the message's `icon`), always downloaded into the icon cache
     pub icon_url: Option<String>,
+    /// Local icon file from the topic's settings; takes precedence over `icon_url`
+    pub icon_path: Option<String>,
+    /// Notifications with the same tag replace each other instead of stacking
+    pub tag: Option<String>,
+    /// URL opened when the notification is clicked (the message's `click` field)
+    pub click: Option<String>,
+    /// Server the message came from; clicking opens the topic there when there is no click URL
+    pub server: Option<String>,
+    /// Action buttons (see `actions::buttons`)
+    pub actions: Vec<NtfyAction>,
+}
+
+/// The user clicked a notification shown by this app
+#[derive(Debug, Clone)]
+pub struct NotificationActivation {
+    pub data: NotificationData,
+    /// Index into `data.actions` when a button was clicked rather than the notification
+    pub action: Option<usize>,
+}
+
+static ACTIVATIONS: OnceCell<broadcast::Sender<NotificationActivation>> = OnceCell::const_new();
+
+fn activation_sender() -> &'static broadcast::Sender<NotificationActivation> {
+    if ACTIVATIONS.get().is_none() {
+        // Another thread may win the race; its sender is used then
+        let _ = ACTIVATIONS.set(broadcast::channel(16).0);
+    }
+    ACTIVATIONS.get().expect("activation channel initialized")
+}
+
+/// Clicks on notifications from every backend
+pub fn subscribe_activations() -> broadcast::Receiver<NotificationActivation> {
+    activation_sender().subscribe()
+}
+
+/// Report a click from a notification backend
+pub fn notify_activated(data: NotificationData, action: Option<usize>) {
+    let _ = activation_sender().send(NotificationActivation { data, action });
 }
 
 impl Default for NotificationManager {
@@ -49,6 +90,7 @@ impl NotificationManager {
             sound: sound.clone(),
             persistent,
             icon_url: None,
+            ..Default::default()
         };
         self.show_notification_full(&data).await
     }
@@ -56,6 +98,13 @@ impl NotificationManager {
     pub async fn show_notification_full(&self, data: &NotificationData) -> Result<()> {
         println!("DEBUG: show_notification called - title: {}", data.title);
 
+        // The toast itself stays silent; a missing or broken file must not lose the notification
+        if let NotificationSound::Custom(path) = &data.sound {
+            if let Err(e) = crate::sounds::play(std::path::Path::new(path)) {
+                eprintln!("Failed to play custom sound: {}", e);
+            }
+        }
+
         #[cfg(target_os = "windows")]
         {
             self.show_notification_windows(data).await?;
@@ -74,13 +123,10 @@ impl NotificationManager {
 
         #[cfg(target_os = "linux")]
         {
-            use std::process::Command;
-            let mut cmd = Command::new("notify-send");
-            cmd.arg(&data.title).arg(&data.message);
-            if data.urgent {
-                cmd.arg("-u").arg("critical");
-            }
-            let _ = cmd.output();
+            let icon_path = self.get_notification_icon_path(data).await;
+            let notifier = crate::dbus_notifications::DbusNotifier::shared().await?;
+            let id = notifier.show(data, icon_path.as_deref()).await?;
+            println!("DEBUG: D-Bus notification shown (id: {})", id);
         }
 
         Ok(())
@@ -116,7 +162,7 @@ impl NotificationManager {
 
         // Icon (must be PNG, max 1024x1024, <= 200KB)
         // Try cached remote icon first, then fall back to local app icon
-        if let Some(icon_path) = self.get_notification_icon_path(&data.icon_url).await {
+        if let Some(icon_path) = self.get_notification_icon_path(data).await {
             println!("DEBUG: Using notification icon: {}", icon_path);
             args.push("-p".to_string());
             args.push(icon_path);
@@ -124,7 +170,7 @@ impl NotificationManager {
 
         // Sound
         match &data.sound {
-            NotificationSound::None => {
+            NotificationSound::None | NotificationSound::Custom(_) => {
                 args.push("-silent".to_string());
             }
             NotificationSound::Alert => {
@@ -149,6 +195,13 @@ impl NotificationManager {
             }
         }
 
+        // Action buttons; ntfytoast separates labels with ';'
+        if !data.actions.is_empty() {
+            let labels: Vec<String> = data.actions.iter().map(|a| a.label.replace(';', ",")).collect();
+            args.push("-b".to_string());
+            args.push(labels.join(";"));
+        }
+
         // Persistent notifications
         if data.persistent {
             args.push("-persistent".to_string());
@@ -191,10 +244,28 @@ impl NotificationManager {
         }
 
         // Exit codes:
-        // 0 = Success, 1 = Hidden, 2 = Dismissed, 3 = TimedOut, 4 = ButtonPressed
+        // 0 = Clicked, 1 = Hidden, 2 = Dismissed, 3 = TimedOut, 4 = ButtonPressed
         // 5 = TextEntered, -1 = Failed
         match exit_code {
-            0 | 1 | 2 | 3 | 4 | 5 => {
+            0 => {
+                println!("Windows toast notification clicked");
+                notify_activated(data.clone(), None);
+            }
+            4 => {
+                // ntfytoast prints the label of the pressed button
+                let pressed = data
+                    .actions
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, a)| stdout.contains(a.label.replace(';', ",").as_str()))
+                    .max_by_key(|(_, a)| a.label.len())
+                    .map(|(i, _)| i);
+                println!("Windows toast button pressed: {:?}", pressed);
+                if pressed.is_some() {
+                    notify_activated(data.clone(), pressed);
+                }
+            }
+            1 | 2 | 3 | 5 => {
                 println!("Windows toast notification shown successfully (exit code: {})", exit_code);
             }
             _ => {
@@ -225,7 +296,7 @@ impl NotificationManager {
         };
 
         let sound_name: Option<&str> = match &data.sound {
-            NotificationSound::None => None,
+            NotificationSound::None | NotificationSound::Custom(_) => None,
             NotificationSound::Alert => Some("Notification.Default"),
             NotificationSound::Bell => Some("Notification.IM"),
             NotificationSound::Chime => Some("Notification.SMS"),
@@ -323,7 +394,7 @@ impl NotificationManager {
 
     /// Download and cache remote icon for notifications
     /// Returns local path to cached icon file (resized to 128x128 PNG)
-    #[cfg(target_os = "windows")]
+    #[cfg(any(target_os = "windows", target_os = "linux"))]
     async fn get_cached_icon_path(&self, icon_url: &str) -> Option<String> {
         // Get cache directory
         let cache_dir = dirs::config_dir()
@@ -485,12 +556,18 @@ impl NotificationManager {
     }
 
     /// Get the best available icon for notification
-    /// 1. Try cached remote icon if icon_url provided
-    /// 2. Fall back to local app icon
-    #[cfg(target_os = "windows")]
-    async fn get_notification_icon_path(&self, icon_url: &Option<String>) -> Option<String> {
+    /// 1. Use the topic's local icon file if set
+    /// 2. Try cached remote icon if icon_url provided
+    /// 3. Fall back to local app icon
+    #[cfg(any(target_os = "windows", target_os = "linux"))]
+    async fn get_notification_icon_path(&self, data: &NotificationData) -> Option<String> {
+        // Only the topic settings may point at local files; publishers control icon_url
+        if let Some(path) = data.icon_path.as_deref().filter(|p| std::path::Path::new(p).is_file()) {
+            return Some(path.to_string());
+        }
+
         // Try remote icon if provided
-        if let Some(url) = icon_url {
+        if let Some(url) = &data.icon_url {
             if let Some(cached) = self.get_cached_icon_path(url).await {
                 return Some(cached);
             }
@@ -531,7 +608,29 @@ pub async fn show_notification_with_icon(
         sound: sound.clone(),
         persistent,
         icon_url: icon_url.map(|s| s.to_string()),
+        ..Default::default()
     };
     manager.show_notification_full(&data).await
 }
 
+
+#[cfg(all(test, any(target_os = "windows", target_os = "linux")))]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_only_topic_icons_may_be_local_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("secret.png");
+        std::fs::write(&file, b"not really a png").unwrap();
+        let file = file.to_str().unwrap().to_string();
+        let manager = NotificationManager::new();
+
+        // A publisher-supplied icon is never read from disk
+        let from_message = NotificationData { icon_url: Some(file.clone()), ..Default::default() };
+        assert_ne!(manager.get_notification_icon_path(&from_message).await, Some(file.clone()));
+
+        let from_topic = NotificationData { icon_path: Some(file.clone()), ..Default::default() };
+        assert_eq!(manager.get_notification_icon_path(&from_topic).await, Some(file));
+    }
+}
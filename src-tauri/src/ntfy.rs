@@ -1,15 +1,28 @@
 use anyhow::Result;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
-use tauri::{AppHandle, Emitter};
+use tauri::{AppHandle, Emitter, Manager};
 use tokio::sync::Mutex;
 use tokio::time::{sleep, Duration};
 
+use super::alarm::{Alarm, AlarmState};
+use super::burst::{self, BurstDecision, BurstTracker};
 use super::config::AppConfig;
-use super::notifications::NotificationManager;
+use super::crypto::{self, DecryptionStatus, TopicKey};
+use super::email::EmailForwarder;
+use super::history::HistoryStore;
+use super::hooks::HookRunner;
+use super::notifications::{NotificationData, NotificationManager};
+use super::polling_state::{ConnectionStatus, SharedPollingState};
+use super::priority::PriorityPolicy;
+use super::quiet_hours::{DigestEntry, DigestQueue, QuietDecision};
+use super::rate_limit::{self, RateDecision, RateLimiter, RateLimits};
+use super::rules::RuleSet;
+use super::subscriptions::TopicSettings;
+use super::webhook::WebhookForwarder;
 
 /// Raw message from ntfy NDJSON response
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -31,14 +44,62 @@ pub struct NtfyMessage {
     pub actions: Option<Vec<NtfyAction>>,
     #[serde(default)]
     pub icon: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attachment: Option<NtfyAttachment>,
+    /// "base64" for binary payloads (e.g. UnifiedPush messages)
+    #[serde(default)]
+    pub encoding: Option<String>,
+    /// Set locally when the message arrived on an encrypted topic
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub decryption: Option<DecryptionStatus>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct NtfyAction {
+    /// "view", "http" or "broadcast"
     pub action: String,
     pub label: String,
     pub url: Option<String>,
+    /// Dismiss the notification once the action ran
     pub clear: Option<bool>,
+    /// HTTP method for "http" actions (POST when unset)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub method: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub headers: Option<std::collections::BTreeMap<String, String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body: Option<String>,
+    /// Broadcast intent name (Android); sent along with the desktop event
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub intent: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extras: Option<std::collections::BTreeMap<String, String>>,
+}
+
+/// File attached to a message (hosted by the ntfy server or an external URL)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NtfyAttachment {
+    pub name: String,
+    pub url: String,
+    #[serde(default, rename = "type", skip_serializing_if = "Option::is_none")]
+    pub content_type: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires: Option<u64>,
+}
+
+/// Message to publish via the ntfy JSON API
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PublishRequest {
+    pub topic: String,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub priority: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tags: Option<Vec<String>>,
 }
 
 pub struct NtfyClient {
@@ -145,6 +206,23 @@ impl NtfyClient {
         Ok(messages)
     }
 
+    /// Publish a message using the JSON API: POST {base_url}/ with the topic in the body
+    pub async fn publish(&self, request: &PublishRequest) -> Result<()> {
+        let url = format!("{}/", self.base_url);
+        let response = self.apply_auth(self.client.post(&url).json(request)).send().await?;
+        let status = response.status();
+
+        if status == reqwest::StatusCode::UNAUTHORIZED || status == reqwest::StatusCode::FORBIDDEN {
+            return Err(anyhow::anyhow!("Not allowed to publish to '{}' ({}) - check your credentials", request.topic, status));
+        }
+
+        if !status.is_success() {
+            return Err(anyhow::anyhow!("HTTP {}: {}", status, response.text().await.unwrap_or_default()));
+        }
+
+        Ok(())
+    }
+
     /// Test connection to the ntfy instance
     pub async fn test_connection(&self, topic: &str) -> Result<bool> {
         let url = format!("{}/{}/json?poll=1&since=0", self.base_url, topic);
@@ -168,15 +246,186 @@ impl NtfyClient {
     }
 }
 
+/// Decrypt the title and body of a message on an encrypted topic in place.
+/// Returns None when the message carries no encrypted fields (e.g. published by another client).
+/// On failure the ciphertext is left untouched so the message can be flagged instead of shown.
+pub fn decrypt_message(msg: &mut NtfyMessage, key: Option<&TopicKey>) -> Option<DecryptionStatus> {
+    let title_encrypted = msg.title.as_deref().map_or(false, crypto::is_encrypted);
+    let body_encrypted = msg.message.as_deref().map_or(false, crypto::is_encrypted);
+    if !title_encrypted && !body_encrypted {
+        return None;
+    }
+
+    let Some(key) = key else {
+        msg.decryption = Some(DecryptionStatus::Failed);
+        return msg.decryption;
+    };
+
+    let decrypt_field = |field: &Option<String>, encrypted: bool| -> Result<Option<String>> {
+        match field {
+            Some(value) if encrypted => crypto::decrypt(key, value).map(Some),
+            other => Ok(other.clone()),
+        }
+    };
+
+    let status = match (
+        decrypt_field(&msg.title, title_encrypted),
+        decrypt_field(&msg.message, body_encrypted),
+    ) {
+        (Ok(title), Ok(message)) => {
+            msg.title = title;
+            msg.message = message;
+            DecryptionStatus::Decrypted
+        }
+        (Err(e), _) | (_, Err(e)) => {
+            eprintln!("Failed to decrypt message {:?}: {}", msg.id, e);
+            DecryptionStatus::Failed
+        }
+    };
+
+    msg.decryption = Some(status);
+    msg.decryption
+}
+
+/// Load the key for an encrypted topic from the OS keychain
+fn load_topic_key(topic: &str) -> Option<TopicKey> {
+    match crate::credentials::load_topic_key(topic) {
+        Ok(encoded) if !encoded.is_empty() => match crypto::decode_key(&encoded) {
+            Ok(key) => Some(key),
+            Err(e) => {
+                eprintln!("Invalid encryption key stored for topic '{}': {}", topic, e);
+                None
+            }
+        },
+        Ok(_) => {
+            eprintln!("No encryption key stored for encrypted topic '{}'", topic);
+            None
+        }
+        Err(e) => {
+            eprintln!("Failed to load encryption key for topic '{}': {}", topic, e);
+            None
+        }
+    }
+}
+
+fn save_digest(path: Option<&std::path::Path>, digest: &DigestQueue) {
+    if let Some(path) = path {
+        if let Err(e) = digest.save(path) {
+            eprintln!("Failed to save quiet hours digest: {}", e);
+        }
+    }
+}
+
+/// Summarize messages held back during quiet hours in one toast
+fn show_quiet_digest(digest: &DigestQueue) {
+    let Some((title, body)) = digest.summary() else { return };
+    let data = crate::notifications::NotificationData {
+        title: title.clone(),
+        subtitle: None,
+        message: body,
+        topic: title,
+        timestamp: 0,
+        urgent: false,
+        sound: crate::config::NotificationSound::Default,
+        persistent: false,
+        icon_url: None,
+        // A newer digest replaces one still on screen
+        tag: Some("quiet-digest".to_string()),
+        ..Default::default()
+    };
+    // Shown from a separate task: ntfytoast blocks until the toast is dismissed
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = NotificationManager::new().show_notification_full(&data).await {
+            eprintln!("Failed to show quiet hours digest: {}", e);
+        }
+    });
+}
+
+/// Mute a topic that floods the desktop and say so, once. Topics without a
+/// subscription entry can't be muted; the rate limiter keeps throttling them.
+async fn mute_flooding_topic(
+    app_handle: &AppHandle,
+    config: &Arc<Mutex<AppConfig>>,
+    topic: &str,
+    topic_label: &str,
+    limits: &RateLimits,
+) {
+    let until = chrono::Utc::now().timestamp().max(0) as u64 + limits.mute_minutes * 60;
+    let snapshot = {
+        let mut cfg = config.lock().await;
+        if !cfg.mute_topic_until(topic, until) {
+            println!("Topic '{}' is flooding but not subscribed, throttling only", topic);
+            return;
+        }
+        cfg.clone()
+    };
+    if let Err(e) = crate::config::save_config(app_handle, snapshot).await {
+        eprintln!("Failed to save muted topic: {}", e);
+    }
+    println!("Topic '{}' is flooding, muted for {} minutes", topic, limits.mute_minutes);
+
+    let (title, body) = rate_limit::flood_notice(topic_label, limits.mute_minutes);
+    let data = NotificationData {
+        title,
+        message: body,
+        topic: topic.to_string(),
+        tag: Some(format!("flood:{}", topic)),
+        ..Default::default()
+    };
+    // Shown from a separate task: ntfytoast blocks until the toast is dismissed
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = NotificationManager::new().show_notification_full(&data).await {
+            eprintln!("Failed to show flooding notice: {}", e);
+        }
+    });
+}
+
+/// Flash the tray and raise an alarm as the message's priority policy asks
+fn escalate(app_handle: &AppHandle, policy: &PriorityPolicy, msg: &NtfyMessage, data: &NotificationData, priority: u8) {
+    if policy.flash_tray {
+        let _ = app_handle.emit("tray-flash", &data.topic);
+    }
+    let (true, Some(id), Some(alarms)) = (policy.alarm, msg.id.clone(), app_handle.try_state::<AlarmState>()) else {
+        return;
+    };
+    let alarm = Alarm {
+        id,
+        topic: data.topic.clone(),
+        title: data.title.clone(),
+        message: data.message.clone(),
+        priority,
+        time: msg.time,
+        sound: data.sound.clone(),
+        snoozed_until: None,
+        // The toast shown now is the first ring; without one the alarm loop rings right away
+        rang_at: if policy.popup { chrono::Utc::now().timestamp().max(0) as u64 } else { 0 },
+    };
+    if alarms.raise(alarm) {
+        let _ = app_handle.emit("alarm-raised", &data.topic);
+    }
+}
+
 /// Main polling loop that runs in the background
 pub async fn start_polling(
     app_handle: AppHandle,
     client: Arc<Mutex<NtfyClient>>,
-    _notification_manager: Arc<Mutex<NotificationManager>>,
+    notification_manager: Arc<Mutex<NotificationManager>>,
     config: Arc<Mutex<AppConfig>>,
     is_polling: Arc<AtomicBool>,
 ) {
     let mut seen_ids: HashSet<String> = HashSet::new();
+    let polling_state = match app_handle.try_state::<SharedPollingState>() {
+        Some(state) => state.inner().clone(),
+        None => SharedPollingState::default(),
+    };
+    let digest_path = app_handle.path().app_data_dir().ok().map(|dir| dir.join("quiet_digest.json"));
+    let mut digest = digest_path.as_deref().map(DigestQueue::load).unwrap_or_default();
+    let forwarder = WebhookForwarder::default();
+    let mut mailer = EmailForwarder::default();
+    let mut bursts = BurstTracker::default();
+    let mut limiter = RateLimiter::default();
+    #[cfg(target_os = "linux")]
+    let journal = crate::journal::JournalSink::default();
 
     // Cleanup interval - clear seen_ids every hour to prevent memory leak
     let cleanup_interval = Duration::from_secs(3600);
@@ -196,10 +445,19 @@ pub async fn start_polling(
             auth_user,
             auth_pass,
             base_url,
-            urgent_threshold,
-            notification_sound,
-            urgent_notification_sound,
-            persistent_notifications_mode,
+            topic_settings,
+            default_settings,
+            encrypted_topics,
+            history_limits,
+            topics,
+            mut pause,
+            pause_allow_urgent,
+            quiet_hours,
+            burst_settings,
+            rate_limits,
+            rules,
+            webhooks,
+            email,
         ) = {
             let cfg = config.lock().await;
             (
@@ -209,13 +467,73 @@ pub async fn start_polling(
                 cfg.auth_user.clone(),
                 cfg.auth_pass.clone(),
                 cfg.api_base_url(),
-                cfg.urgent_priority_threshold,
-                cfg.notification_sound.clone(),
-                cfg.urgent_notification_sound.clone(),
-                cfg.persistent_notifications_mode.clone(),
+                cfg.topics
+                    .iter()
+                    .map(|s| (s.topic.clone(), cfg.topic_settings(&s.topic)))
+                    .collect::<HashMap<String, TopicSettings>>(),
+                // Topics without a subscription (UnifiedPush) use the global settings
+                cfg.topic_settings(""),
+                cfg.encrypted_topics.clone(),
+                if cfg.history_enabled {
+                    Some((cfg.history_max_messages, cfg.history_retention_days))
+                } else {
+                    None
+                },
+                cfg.topics_list(),
+                cfg.pause.clone(),
+                cfg.pause_allow_urgent,
+                cfg.quiet_hours.clone(),
+                cfg.burst.clone(),
+                cfg.rate_limits.clone(),
+                RuleSet::compile(&cfg.rules).unwrap_or_else(|e| {
+                    eprintln!("Ignoring rules: {}", e);
+                    RuleSet::default()
+                }),
+                cfg.webhooks.clone(),
+                cfg.email.clone(),
             )
         };
 
+        let now = chrono::Utc::now().timestamp().max(0) as u64;
+        if pause.as_ref().map_or(false, |p| crate::pause::is_expired(p, now)) {
+            crate::pause::resume(&app_handle, &config).await;
+            pause = None;
+        }
+        // Quiet hours are over: show what was held back (unless a pause is still running)
+        if !digest.entries.is_empty() && pause.is_none() && !quiet_hours.is_active(chrono::Utc::now()) {
+            show_quiet_digest(&digest);
+            digest = DigestQueue::default();
+            save_digest(digest_path.as_deref(), &digest);
+        }
+
+        polling_state.update_and_emit(&app_handle, |state| {
+            state.paused_until = pause.as_ref().map(|p| p.until);
+            state.topics = topics.clone();
+            if topics.is_empty() {
+                state.connection = ConnectionStatus::Idle;
+            }
+        });
+
+        // Topics subscribed on behalf of apps registered with the UnifiedPush distributor
+        #[cfg(target_os = "linux")]
+        let topics_path = {
+            let push_topics = match app_handle.try_state::<crate::unifiedpush::UnifiedPushState>() {
+                Some(push) => push.topics().await,
+                None => Vec::new(),
+            };
+            let mut topics: Vec<String> = topics_path
+                .split(',')
+                .filter(|t| !t.is_empty())
+                .map(String::from)
+                .collect();
+            for topic in push_topics {
+                if !topics.contains(&topic) {
+                    topics.push(topic);
+                }
+            }
+            topics.join(",")
+        };
+
         if topics_path.is_empty() {
             is_polling.store(false, Ordering::SeqCst);
             sleep(Duration::from_secs(5)).await;
@@ -225,7 +543,7 @@ pub async fn start_polling(
         // Update client auth and base_url if changed
         {
             let mut client_lock = client.lock().await;
-            client_lock.base_url = base_url;
+            client_lock.base_url = base_url.clone();
             client_lock.api_token = if !api_token.is_empty() { Some(api_token) } else { None };
             client_lock.auth_user = if !auth_user.is_empty() { Some(auth_user) } else { None };
             client_lock.auth_pass = if !auth_pass.is_empty() { Some(auth_pass) } else { None };
@@ -239,7 +557,15 @@ pub async fn start_polling(
         match result {
             Ok(messages) => {
                 let mut new_count = 0u32;
-                for msg in messages {
+                let mut stored_count = 0u32;
+                let mut recent: Vec<NtfyMessage> = Vec::new();
+                let mut digest_changed = false;
+                // Keys are looked up lazily, once per topic per poll
+                let mut topic_keys: HashMap<String, Option<TopicKey>> = HashMap::new();
+                // Latest summary per bursting topic, shown once the whole poll is processed
+                let mut summaries: HashMap<String, NotificationData> = HashMap::new();
+
+                for mut msg in messages {
                     let msg_id = match &msg.id {
                         Some(id) => id.clone(),
                         None => continue,
@@ -250,14 +576,42 @@ pub async fn start_polling(
                     }
 
                     seen_ids.insert(msg_id);
+
+                    // Push messages go to the registered app, not the desktop
+                    #[cfg(target_os = "linux")]
+                    if let Some(push) = app_handle.try_state::<crate::unifiedpush::UnifiedPushState>() {
+                        if push.deliver(&msg).await {
+                            continue;
+                        }
+                    }
+
                     new_count += 1;
 
+                    // Decrypt before anything else sees the message
+                    if let Some(topic) = msg.topic.clone().filter(|t| encrypted_topics.contains(t)) {
+                        let key = topic_keys
+                            .entry(topic)
+                            .or_insert_with_key(|topic| load_topic_key(topic));
+                        decrypt_message(&mut msg, key.as_ref());
+                    }
+
+                    if history_limits.is_some() {
+                        if let Some(history) = app_handle.try_state::<HistoryStore>() {
+                            match history.insert(&base_url, &msg) {
+                                Ok(true) => stored_count += 1,
+                                Ok(false) => {}
+                                Err(e) => eprintln!("Failed to store message in history: {}", e),
+                            }
+                        }
+                    }
+
                     // Format notification like Electron app:
                     // title: "{topic} - {date}" (or "{topic} - {msg_title}" if available)
                     // message: the actual message
                     let topic = msg.topic.clone().unwrap_or_else(|| "ntfy".to_string());
                     let message_body = msg.message.clone().unwrap_or_default();
-                    let msg_title = msg.title.clone();
+                    let outcome = rules.evaluate(&msg);
+                    let msg_title = outcome.title.clone().or_else(|| msg.title.clone());
 
                     // Format date like Electron app: "YYYY-MM-DD hh:mm a"
                     let datetime = chrono::DateTime::from_timestamp(msg.time as i64, 0)
@@ -266,40 +620,69 @@ pub async fn start_polling(
 
                     // Title: use message title if available, otherwise "New Notification"
                     // Topic is shown separately in the message body
-                    let notification_title = if let Some(ref title) = msg_title {
-                        if !title.is_empty() {
-                            title.clone()
-                        } else {
-                            "New Notification".to_string()
-                        }
+                    let undecryptable = msg.decryption == Some(DecryptionStatus::Failed);
+                    // Like the ntfy apps: emoji tags prefix the title (or the body when there is
+                    // no title), other tags are listed in the body
+                    let (tag_emojis, other_tags) = crate::tags::split(msg.tags.as_deref().unwrap_or_default());
+                    let has_title = msg_title.as_deref().map_or(false, |t| !t.is_empty());
+                    let notification_title = if undecryptable {
+                        "🔒 Undecryptable message".to_string()
+                    } else if let Some(title) = msg_title.filter(|t| !t.is_empty()) {
+                        crate::tags::prefix(&tag_emojis, &title)
                     } else {
                         "New Notification".to_string()
                     };
 
                     // Show native OS notification
-                    let priority = msg.priority.unwrap_or(3);
-                    let urgent = priority >= urgent_threshold;
-                    let sound = if urgent {
-                        &urgent_notification_sound
+                    let settings = topic_settings.get(&topic).unwrap_or(&default_settings);
+
+                    // Hooks run for every new message, whether or not a toast is shown
+                    if let Some(runner) = app_handle.try_state::<HookRunner>() {
+                        for hook in settings.hook.iter().chain(outcome.hooks.iter()) {
+                            runner.spawn(hook.clone(), msg.clone());
+                        }
+                    }
+                    forwarder.forward(&webhooks, &msg);
+                    mailer.forward(&email, &msg);
+                    #[cfg(target_os = "linux")]
+                    if settings.journal {
+                        if let Err(e) = journal.write(&msg) {
+                            eprintln!("{}", e);
+                        }
+                    }
+                    let topic_label = if topic_settings.contains_key(&topic) { &settings.display_name } else { &topic };
+                    let priority = outcome.priority.or(msg.priority).unwrap_or(3);
+                    let policy = settings.policy(priority, outcome.urgent);
+                    let urgent = outcome.urgent.unwrap_or(policy.urgent);
+                    let sound = outcome.sound.as_ref().unwrap_or(&policy.sound);
+                    let persistent = outcome.persistent || policy.persistent;
+
+                    // Format message with topic at the end
+                    let tag_line = if other_tags.is_empty() {
+                        String::new()
                     } else {
-                        &notification_sound
+                        format!("Tags: {}\n", other_tags.join(", "))
                     };
-                    let persistent = match persistent_notifications_mode {
-                        crate::config::PersistentNotificationMode::Off => false,
-                        crate::config::PersistentNotificationMode::All => true,
-                        crate::config::PersistentNotificationMode::UrgentOnly => urgent,
+                    let message_body = if has_title { message_body } else { crate::tags::prefix(&tag_emojis, &message_body) };
+                    let formatted_message = if undecryptable {
+                        format!("Could not decrypt this message. Check the passphrase for this topic.\n\nTopic: {}", topic_label)
+                    } else if message_body.is_empty() {
+                        format!("{}Topic: {}", tag_line, topic_label)
+                    } else {
+                        format!("{}\n\n{}Topic: {}", message_body, tag_line, topic_label)
                     };
 
-                    // Format message with topic at the end
-                    let formatted_message = if message_body.is_empty() {
-                        format!("Topic: {}", topic)
-                    } else {
-                        format!("{}\n\nTopic: {}", message_body, topic)
+                    // `topic` moves into the notification below
+                    let label = topic_label.clone();
+
+                    // A topic icon may be a URL or a local file; message icons are always downloaded
+                    let (topic_icon_url, topic_icon_path) = match settings.icon.clone() {
+                        Some(icon) if icon.starts_with("http://") || icon.starts_with("https://") => (Some(icon), None),
+                        icon => (None, icon),
                     };
 
                     // Use full notification data for better formatting
-                    use crate::notifications::NotificationData;
-                    let notification_data = NotificationData {
+                    let mut notification_data = NotificationData {
                         title: notification_title,
                         subtitle: Some(datetime),
                         message: formatted_message,
@@ -308,28 +691,136 @@ pub async fn start_polling(
                         urgent,
                         sound: sound.clone(),
                         persistent,
-                        icon_url: msg.icon.clone(),
+                        icon_url: topic_icon_url.or_else(|| msg.icon.clone()),
+                        icon_path: topic_icon_path,
+                        tag: None,
+                        click: msg.click.clone().filter(|c| !c.is_empty()),
+                        server: Some(base_url.clone()),
+                        actions: crate::actions::buttons(msg.actions.as_deref().unwrap_or_default()),
                     };
 
-                    // Native notifications are now handled by the web UI's Notification API bridge
-                    // (see initialization_script in main.rs). Polling loop only tracks badge counts.
-                    let _ = notification_data;
+                    // Paused, muted or quiet-hours messages are still stored and counted, just not shown
+                    let quiet = if policy.bypass_quiet_hours {
+                        QuietDecision::Show
+                    } else {
+                        quiet_hours.decide(priority, chrono::Utc::now())
+                    };
+                    if outcome.suppress {
+                        println!("Notification for '{}' suppressed by rule {:?}", notification_data.topic, outcome.matched);
+                    } else if crate::pause::suppresses(pause.as_ref(), pause_allow_urgent, priority)
+                        || settings.muted
+                    {
+                        println!("Notification for '{}' suppressed (paused or muted)", notification_data.topic);
+                    } else if quiet == QuietDecision::Suppress {
+                        println!("Notification for '{}' held back for quiet hours", notification_data.topic);
+                        if quiet_hours.digest {
+                            digest.entries.push(DigestEntry {
+                                topic: notification_data.topic.clone(),
+                                title: notification_data.title.clone(),
+                                priority,
+                                time: msg.time,
+                            });
+                            digest_changed = true;
+                        }
+                    } else {
+                        // Alarms and tray flashing don't depend on who shows the toast
+                        escalate(&app_handle, policy, &msg, &notification_data, priority);
+
+                        if !policy.popup {
+                            println!(
+                                "Notification for '{}' stored without a popup (priority {})",
+                                notification_data.topic, priority
+                            );
+                        } else {
+                            if quiet == QuietDecision::Downgrade {
+                                notification_data.sound = crate::config::NotificationSound::None;
+                                notification_data.persistent = false;
+                            }
+
+                            // Throttled messages stay in history; a flooding topic is muted once
+                            let now_ms = chrono::Utc::now().timestamp_millis().max(0) as u64;
+                            let rate = limiter.check(&rate_limits, &notification_data.topic, now_ms);
+                            if rate == RateDecision::Flooding {
+                                mute_flooding_topic(&app_handle, &config, &notification_data.topic, &label, &rate_limits)
+                                    .await;
+                            }
+                            if rate != RateDecision::Allow {
+                                println!("Notification for '{}' throttled", notification_data.topic);
+                            } else if let BurstDecision::Summarize { count } =
+                                bursts.record(&burst_settings, &notification_data.topic, urgent, now_ms / 1000)
+                            {
+                                let summary = burst::summary(&notification_data, count, &label);
+                                summaries.insert(notification_data.topic.clone(), summary);
+                            } else {
+                                // Show from a separate task: ntfytoast blocks until the toast is dismissed.
+                                // The web UI bridge skips subscribed topics (see show_notification in main.rs).
+                                let manager = notification_manager.lock().await.clone();
+                                tauri::async_runtime::spawn(async move {
+                                    if let Err(e) = manager.show_notification_full(&notification_data).await {
+                                        eprintln!("Failed to show notification: {}", e);
+                                    }
+                                });
+                            }
+                        }
+                    }
+
+                    recent.push(msg.clone());
 
-                    // Emit event for badge count tracking
+                    // Emit event for badge count tracking (carries the decrypted message)
                     let _ = app_handle.emit("new-notification", &msg);
                 }
 
+                if digest_changed {
+                    save_digest(digest_path.as_deref(), &digest);
+                }
+
+                // Replaces the topic's previous summary where the platform supports it
+                for summary in summaries.into_values() {
+                    let manager = notification_manager.lock().await.clone();
+                    tauri::async_runtime::spawn(async move {
+                        if let Err(e) = manager.show_notification_full(&summary).await {
+                            eprintln!("Failed to show burst summary: {}", e);
+                        }
+                    });
+                }
+
+                polling_state.update_and_emit(&app_handle, |state| {
+                    state.connection = ConnectionStatus::Connected;
+                    for msg in &recent {
+                        state.push_recent(&base_url, msg);
+                    }
+                });
+
+                // Lets the notification center refresh without polling the store
+                if stored_count > 0 {
+                    let _ = app_handle.emit("history-updated", stored_count);
+                }
+
                 if new_count > 0 {
                     let _ = app_handle.emit("badge-update", new_count);
+
+                    if let (Some((max_messages, retention_days)), Some(history)) =
+                        (history_limits, app_handle.try_state::<HistoryStore>())
+                    {
+                        if let Err(e) = history.apply_retention(max_messages, retention_days) {
+                            eprintln!("Failed to apply history retention: {}", e);
+                        }
+                    }
                 }
             }
             Err(e) => {
                 eprintln!("Polling error: {}", e);
+                polling_state.update_and_emit(&app_handle, |state| {
+                    state.connection = ConnectionStatus::Error { message: e.to_string() };
+                });
             }
         }
 
         is_polling.store(false, Ordering::SeqCst);
 
+        // Email digests go out on their own interval, independent of the poll rate
+        mailer.flush(&email);
+
         // Periodic cleanup of seen_ids
         if last_cleanup.elapsed() > cleanup_interval {
             seen_ids.clear();
@@ -344,3 +835,64 @@ pub async fn start_polling(
         sleep(Duration::from_secs(poll_rate)).await;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_key(passphrase: &str, topic: &str) -> TopicKey {
+        crypto::derive_key_with_rounds(passphrase, topic, 1_000)
+    }
+
+    fn message(title: Option<&str>, body: Option<&str>) -> NtfyMessage {
+        serde_json::from_value(serde_json::json!({
+            "id": "abc123",
+            "time": 1700000000,
+            "event": "message",
+            "topic": "deploys",
+            "title": title,
+            "message": body,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_decrypt_message() {
+        let key = test_key("secret", "deploys");
+        let mut msg = message(
+            Some(&crypto::encrypt(&key, "Deploy finished").unwrap()),
+            Some(&crypto::encrypt(&key, "v1.2.3 is live").unwrap()),
+        );
+
+        assert_eq!(decrypt_message(&mut msg, Some(&key)), Some(DecryptionStatus::Decrypted));
+        assert_eq!(msg.title.as_deref(), Some("Deploy finished"));
+        assert_eq!(msg.message.as_deref(), Some("v1.2.3 is live"));
+    }
+
+    #[test]
+    fn test_decrypt_message_plaintext_passes_through() {
+        let key = test_key("secret", "deploys");
+        let mut msg = message(None, Some("not encrypted"));
+
+        assert_eq!(decrypt_message(&mut msg, Some(&key)), None);
+        assert_eq!(msg.message.as_deref(), Some("not encrypted"));
+        assert!(msg.decryption.is_none());
+    }
+
+    #[test]
+    fn test_decrypt_message_flags_failures() {
+        let key = test_key("secret", "deploys");
+        let ciphertext = crypto::encrypt(&key, "v1.2.3 is live").unwrap();
+
+        let mut wrong_key = message(None, Some(&ciphertext));
+        let other = test_key("guess", "deploys");
+        assert_eq!(decrypt_message(&mut wrong_key, Some(&other)), Some(DecryptionStatus::Failed));
+        assert_eq!(wrong_key.message.as_deref(), Some(ciphertext.as_str()));
+
+        let mut no_key = message(None, Some(&ciphertext));
+        assert_eq!(decrypt_message(&mut no_key, None), Some(DecryptionStatus::Failed));
+
+        let serialized = serde_json::to_string(&no_key).unwrap();
+        assert!(serialized.contains("\"decryption\":\"failed\""));
+    }
+}
@@ -0,0 +1,195 @@
+//! Pausing (snoozing) notifications. The pause lives in `AppConfig` so it
+//! survives restarts; messages keep being received and stored meanwhile, and
+//! resuming shows a summary of what arrived.
+
+use chrono::{DateTime, Duration, Local, TimeZone};
+use std::sync::Arc;
+use tauri::{AppHandle, Manager};
+use tokio::sync::Mutex;
+
+use super::config::{self, AppConfig, NotificationSound, PauseState};
+use super::history::HistoryStore;
+use super::polling_state::SharedPollingState;
+
+/// Local hour at which "pause until tomorrow" ends
+const TOMORROW_RESUME_HOUR: u32 = 8;
+
+/// Topics named individually in the resume summary
+const SUMMARY_TOPICS: usize = 3;
+
+/// Pause choices offered in the tray menu
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PauseDuration {
+    Minutes(i64),
+    UntilTomorrow,
+}
+
+impl PauseDuration {
+    /// End of the pause (unix seconds) when started at `now`
+    pub fn until(&self, now: DateTime<Local>) -> u64 {
+        let end = match self {
+            PauseDuration::Minutes(minutes) => now + Duration::minutes(*minutes),
+            PauseDuration::UntilTomorrow => {
+                let tomorrow = now.date_naive() + Duration::days(1);
+                let morning = tomorrow.and_hms_opt(TOMORROW_RESUME_HOUR, 0, 0).unwrap_or_default();
+                Local
+                    .from_local_datetime(&morning)
+                    .earliest()
+                    .unwrap_or(now + Duration::days(1))
+            }
+        };
+        end.timestamp().max(0) as u64
+    }
+}
+
+fn now_secs() -> u64 {
+    chrono::Utc::now().timestamp().max(0) as u64
+}
+
+/// Whether a pause has run out at `now`
+pub fn is_expired(pause: &PauseState, now: u64) -> bool {
+    pause.until <= now
+}
+
+/// Whether a message of the given priority is kept from showing by the pause
+pub fn suppresses(pause: Option<&PauseState>, allow_urgent: bool, priority: u8) -> bool {
+    pause.is_some() && !(allow_urgent && priority >= 5)
+}
+
+/// Resume summary, e.g. "14 messages arrived while paused: 9 in ci-alerts, 3 in backups, 2 in deploys"
+pub fn summary(counts: &[(String, u64)]) -> Option<String> {
+    let total: u64 = counts.iter().map(|(_, n)| n).sum();
+    if total == 0 {
+        return None;
+    }
+
+    let mut parts: Vec<String> = counts
+        .iter()
+        .take(SUMMARY_TOPICS)
+        .map(|(topic, n)| format!("{} in {}", n, topic))
+        .collect();
+    let rest: u64 = counts.iter().skip(SUMMARY_TOPICS).map(|(_, n)| n).sum();
+    if rest > 0 {
+        parts.push(format!("{} in other topics", rest));
+    }
+
+    let noun = if total == 1 { "message" } else { "messages" };
+    Some(format!("{} {} arrived while paused: {}", total, noun, parts.join(", ")))
+}
+
+/// Save the config and mirror the pause into the polling state (tray menu)
+async fn store(app_handle: &AppHandle, config: &Arc<Mutex<AppConfig>>, pause: Option<PauseState>) -> AppConfig {
+    let snapshot = {
+        let mut cfg = config.lock().await;
+        cfg.pause = pause.clone();
+        cfg.clone()
+    };
+    if let Err(e) = config::save_config(app_handle, snapshot.clone()).await {
+        eprintln!("Failed to save pause state: {}", e);
+    }
+    if let Some(state) = app_handle.try_state::<SharedPollingState>() {
+        state.update_and_emit(app_handle, |s| s.paused_until = pause.map(|p| p.until));
+    }
+    snapshot
+}
+
+/// Pause toasts until the given time. Extending an active pause keeps its start.
+pub async fn pause(app_handle: &AppHandle, config: &Arc<Mutex<AppConfig>>, until: u64) {
+    let since = match config.lock().await.pause.as_ref() {
+        Some(current) => current.since,
+        None => now_secs(),
+    };
+    println!("Notifications paused until {}", until);
+    store(app_handle, config, Some(PauseState { since, until })).await;
+}
+
+/// End the pause (manually or on expiry) and show what arrived meanwhile
+pub async fn resume(app_handle: &AppHandle, config: &Arc<Mutex<AppConfig>>) {
+    let Some(previous) = config.lock().await.pause.clone() else { return };
+    store(app_handle, config, None).await;
+    println!("Notifications resumed");
+
+    let counts = match app_handle.try_state::<HistoryStore>() {
+        Some(history) => history.topic_counts_since(previous.since).unwrap_or_else(|e| {
+            eprintln!("Failed to summarize paused messages: {}", e);
+            Vec::new()
+        }),
+        None => Vec::new(),
+    };
+    if let Some(body) = summary(&counts) {
+        if let Err(e) = crate::notifications::show_notification(
+            "Notifications resumed",
+            &body,
+            false,
+            &NotificationSound::Default,
+            false,
+        )
+        .await
+        {
+            eprintln!("Failed to show pause summary: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ntfy::NtfyMessage;
+
+    #[test]
+    fn test_pause_durations() {
+        let now = Local.with_ymd_and_hms(2026, 10, 18, 22, 15, 0).unwrap();
+        let start = now.timestamp() as u64;
+
+        assert_eq!(PauseDuration::Minutes(30).until(now), start + 30 * 60);
+        assert_eq!(PauseDuration::Minutes(60).until(now), start + 3600);
+
+        let tomorrow = Local.with_ymd_and_hms(2026, 10, 19, TOMORROW_RESUME_HOUR, 0, 0).unwrap();
+        assert_eq!(PauseDuration::UntilTomorrow.until(now), tomorrow.timestamp() as u64);
+    }
+
+    #[test]
+    fn test_suppression() {
+        let pause = PauseState { since: 1000, until: 2000 };
+
+        assert!(!is_expired(&pause, 1999));
+        assert!(is_expired(&pause, 2000));
+
+        assert!(suppresses(Some(&pause), true, 4));
+        assert!(!suppresses(Some(&pause), true, 5));
+        assert!(suppresses(Some(&pause), false, 5));
+        assert!(!suppresses(None, false, 3));
+    }
+
+    #[test]
+    fn test_summary() {
+        assert_eq!(summary(&[]), None);
+        assert_eq!(
+            summary(&[("alerts".to_string(), 1)]).as_deref(),
+            Some("1 message arrived while paused: 1 in alerts")
+        );
+
+        let counts: Vec<(String, u64)> = [("ci-alerts", 9), ("backups", 3), ("deploys", 2), ("misc", 1), ("other", 1)]
+            .iter()
+            .map(|(t, n)| (t.to_string(), *n))
+            .collect();
+        assert_eq!(
+            summary(&counts).as_deref(),
+            Some("16 messages arrived while paused: 9 in ci-alerts, 3 in backups, 2 in deploys, 2 in other topics")
+        );
+    }
+
+    #[test]
+    fn test_counts_from_history() {
+        let store = HistoryStore::open_in_memory().unwrap();
+        for (id, topic) in [("a", "alerts"), ("b", "backups"), ("c", "alerts")] {
+            let msg: NtfyMessage =
+                serde_json::from_value(serde_json::json!({ "id": id, "time": 1, "topic": topic })).unwrap();
+            store.insert("https://ntfy.sh", &msg).unwrap();
+        }
+
+        let counts = store.topic_counts_since(now_secs() - 60).unwrap();
+        assert_eq!(counts, vec![("alerts".to_string(), 2), ("backups".to_string(), 1)]);
+        assert!(store.topic_counts_since(now_secs() + 60).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,178 @@
+//! Live state of the polling loop (connection status, recent messages, pause),
+//! shared with the tray menu which is rebuilt whenever it changes.
+
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Emitter};
+
+use super::crypto::DecryptionStatus;
+use super::ntfy::NtfyMessage;
+
+/// Number of recent messages kept for the tray menu
+pub const RECENT_LIMIT: usize = 5;
+
+/// Longest message label shown in the tray menu
+const RECENT_LABEL_CHARS: usize = 48;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum ConnectionStatus {
+    /// No topics to poll
+    Idle,
+    Connecting,
+    Connected,
+    Error { message: String },
+}
+
+impl ConnectionStatus {
+    pub fn label(&self) -> String {
+        match self {
+            ConnectionStatus::Idle => "No topics subscribed".to_string(),
+            ConnectionStatus::Connecting => "Connecting…".to_string(),
+            ConnectionStatus::Connected => "● Connected".to_string(),
+            ConnectionStatus::Error { message } => format!("⚠ Connection error: {}", truncate(message, RECENT_LABEL_CHARS)),
+        }
+    }
+}
+
+/// A recently received message, as listed in the tray menu
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RecentMessage {
+    pub id: String,
+    pub server: String,
+    pub topic: String,
+    pub label: String,
+    pub click: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PollingState {
+    pub connection: ConnectionStatus,
+    /// Subscribed topics (excluding internal UnifiedPush topics)
+    pub topics: Vec<String>,
+    /// Newest first
+    pub recent: VecDeque<RecentMessage>,
+    /// End of the current pause (mirrors `AppConfig::pause`)
+    pub paused_until: Option<u64>,
+}
+
+impl Default for PollingState {
+    fn default() -> Self {
+        Self {
+            connection: ConnectionStatus::Connecting,
+            topics: Vec::new(),
+            recent: VecDeque::new(),
+            paused_until: None,
+        }
+    }
+}
+
+/// Managed handle to the polling state
+#[derive(Clone, Default)]
+pub struct SharedPollingState(pub Arc<Mutex<PollingState>>);
+
+impl SharedPollingState {
+    pub fn lock(&self) -> std::sync::MutexGuard<'_, PollingState> {
+        self.0.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Apply a change and report whether anything visible changed
+    pub fn update(&self, change: impl FnOnce(&mut PollingState)) -> bool {
+        let mut state = self.lock();
+        let before = state.clone();
+        change(&mut state);
+        *state != before
+    }
+
+    /// Apply a change and emit `polling-state-changed` (rebuilds the tray menu) if anything changed
+    pub fn update_and_emit(&self, app_handle: &AppHandle, change: impl FnOnce(&mut PollingState)) {
+        if self.update(change) {
+            let snapshot = self.lock().clone();
+            let _ = app_handle.emit("polling-state-changed", &snapshot);
+        }
+    }
+}
+
+fn truncate(text: &str, max_chars: usize) -> String {
+    let text = text.lines().next().unwrap_or_default().trim();
+    if text.chars().count() <= max_chars {
+        text.to_string()
+    } else {
+        format!("{}…", text.chars().take(max_chars - 1).collect::<String>())
+    }
+}
+
+/// Tray label for a message: "topic: title" (falls back to the first line of the body)
+fn recent_label(topic: &str, msg: &NtfyMessage) -> String {
+    let text = if msg.decryption == Some(DecryptionStatus::Failed) {
+        "🔒 Undecryptable message".to_string()
+    } else {
+        [&msg.title, &msg.message]
+            .into_iter()
+            .flatten()
+            .find(|t| !t.trim().is_empty())
+            .cloned()
+            .unwrap_or_else(|| "New Notification".to_string())
+    };
+    truncate(&format!("{}: {}", topic, text), RECENT_LABEL_CHARS)
+}
+
+impl PollingState {
+    /// Add a received message to the recent list
+    pub fn push_recent(&mut self, server: &str, msg: &NtfyMessage) {
+        let Some(id) = msg.id.clone() else { return };
+        let topic = msg.topic.clone().unwrap_or_default();
+        self.recent.retain(|m| m.id != id);
+        self.recent.push_front(RecentMessage {
+            id,
+            server: server.to_string(),
+            label: recent_label(&topic, msg),
+            topic,
+            click: msg.click.clone().filter(|c| !c.is_empty()),
+        });
+        self.recent.truncate(RECENT_LIMIT);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(id: &str, title: Option<&str>, body: &str) -> NtfyMessage {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "time": 1700000000,
+            "topic": "alerts",
+            "title": title,
+            "message": body,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_recent_is_capped_newest_first() {
+        let mut state = PollingState::default();
+        for i in 0..(RECENT_LIMIT + 2) {
+            state.push_recent("https://ntfy.sh", &message(&i.to_string(), None, "body"));
+        }
+        // A repeated id moves to the front instead of appearing twice
+        state.push_recent("https://ntfy.sh", &message("3", None, "body"));
+
+        let ids: Vec<&str> = state.recent.iter().map(|m| m.id.as_str()).collect();
+        assert_eq!(ids, vec!["3", "6", "5", "4", "2"]);
+    }
+
+    #[test]
+    fn test_recent_labels() {
+        let mut state = PollingState::default();
+        state.push_recent("https://ntfy.sh", &message("a", Some("Disk full"), "details"));
+        state.push_recent("https://ntfy.sh", &message("b", Some(""), "first line\nsecond line"));
+        state.push_recent("https://ntfy.sh", &message("c", None, &"x".repeat(100)));
+
+        assert_eq!(state.recent[2].label, "alerts: Disk full");
+        assert_eq!(state.recent[1].label, "alerts: first line");
+        assert_eq!(state.recent[0].label.chars().count(), RECENT_LABEL_CHARS);
+        assert!(state.recent[0].label.ends_with('…'));
+    }
+}
@@ -0,0 +1,152 @@
+//! What a notification does at each of ntfy's five priority levels: popup,
+//! sound, persistence, urgency, tray flashing, bypassing quiet hours and alarm mode.
+
+use serde::{Deserialize, Serialize};
+
+use super::config::{NotificationSound, PersistentNotificationMode};
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct PriorityPolicy {
+    /// Show a toast; without one the message is only stored
+    pub popup: bool,
+    pub sound: NotificationSound,
+    /// Keep the toast until it is dismissed
+    pub persistent: bool,
+    /// Critical toast urgency; never coalesced into burst summaries
+    pub urgent: bool,
+    pub flash_tray: bool,
+    /// Shown normally during quiet hours
+    pub bypass_quiet_hours: bool,
+    /// Repeat until acknowledged in the alarm window (see alarm.rs)
+    pub alarm: bool,
+}
+
+impl Default for PriorityPolicy {
+    fn default() -> Self {
+        Self {
+            popup: true,
+            sound: NotificationSound::Default,
+            persistent: false,
+            urgent: false,
+            flash_tray: false,
+            bypass_quiet_hours: false,
+            alarm: false,
+        }
+    }
+}
+
+/// One policy per priority level, named like ntfy's priorities
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct PriorityPolicies {
+    pub min: PriorityPolicy,
+    pub low: PriorityPolicy,
+    pub default: PriorityPolicy,
+    pub high: PriorityPolicy,
+    pub urgent: PriorityPolicy,
+}
+
+impl Default for PriorityPolicies {
+    fn default() -> Self {
+        Self {
+            min: PriorityPolicy { popup: false, sound: NotificationSound::None, ..Default::default() },
+            low: PriorityPolicy { sound: NotificationSound::None, ..Default::default() },
+            default: PriorityPolicy::default(),
+            high: PriorityPolicy { urgent: true, flash_tray: true, ..Default::default() },
+            urgent: PriorityPolicy {
+                sound: NotificationSound::Alert,
+                persistent: true,
+                urgent: true,
+                flash_tray: true,
+                bypass_quiet_hours: true,
+                alarm: true,
+                ..Default::default()
+            },
+        }
+    }
+}
+
+fn persists(mode: &PersistentNotificationMode, urgent: bool) -> bool {
+    match mode {
+        PersistentNotificationMode::Off => false,
+        PersistentNotificationMode::All => true,
+        PersistentNotificationMode::UrgentOnly => urgent,
+    }
+}
+
+impl PriorityPolicies {
+    /// Equivalent of the single urgency threshold used before per-priority policies
+    pub fn from_threshold(
+        threshold: u8,
+        sound: &NotificationSound,
+        urgent_sound: &NotificationSound,
+        persistence: &PersistentNotificationMode,
+    ) -> Self {
+        let level = |priority: u8| {
+            let urgent = priority >= threshold;
+            PriorityPolicy {
+                sound: if urgent { urgent_sound.clone() } else { sound.clone() },
+                persistent: persists(persistence, urgent),
+                urgent,
+                ..Default::default()
+            }
+        };
+        Self { min: level(1), low: level(2), default: level(3), high: level(4), urgent: level(5) }
+    }
+
+    /// Policy for a priority; out-of-range values are clamped to 1..=5
+    pub fn get(&self, priority: u8) -> &PriorityPolicy {
+        match priority {
+            0 | 1 => &self.min,
+            2 => &self.low,
+            3 => &self.default,
+            4 => &self.high,
+            _ => &self.urgent,
+        }
+    }
+
+    /// Policies from min to urgent
+    pub fn iter(&self) -> impl Iterator<Item = &PriorityPolicy> {
+        [&self.min, &self.low, &self.default, &self.high, &self.urgent].into_iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_policies() {
+        let policies = PriorityPolicies::default();
+        assert!(!policies.get(1).popup);
+        assert_eq!(policies.get(2).sound, NotificationSound::None);
+        assert!(policies.get(3).popup && !policies.get(3).flash_tray);
+        assert!(policies.get(5).persistent && policies.get(5).bypass_quiet_hours && policies.get(5).alarm);
+        assert!(!policies.get(4).alarm);
+        // As with the default urgency threshold of 4 before per-priority policies
+        assert!(policies.get(4).urgent && policies.get(5).urgent && !policies.get(3).urgent);
+        assert_eq!(policies.get(0), policies.get(1));
+        assert_eq!(policies.get(9), policies.get(5));
+    }
+
+    #[test]
+    fn test_threshold_migration() {
+        let policies = PriorityPolicies::from_threshold(
+            4,
+            &NotificationSound::Chime,
+            &NotificationSound::Alert,
+            &PersistentNotificationMode::UrgentOnly,
+        );
+        for priority in 1..=3 {
+            let policy = policies.get(priority);
+            assert!(policy.popup && !policy.persistent && !policy.flash_tray && !policy.bypass_quiet_hours);
+            assert!(!policy.alarm && !policy.urgent);
+            assert_eq!(policy.sound, NotificationSound::Chime);
+        }
+        for priority in 4..=5 {
+            assert_eq!(policies.get(priority).sound, NotificationSound::Alert);
+            assert!(policies.get(priority).persistent && policies.get(priority).urgent);
+        }
+    }
+}
@@ -0,0 +1,312 @@
+//! Quiet hours: weekly schedules during which low-priority toasts are
+//! suppressed (and queued for a digest) or shown silently.
+
+use anyhow::Result;
+use chrono::{DateTime, Datelike, NaiveTime, TimeZone, Timelike, Utc, Weekday};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Digest lines listed individually before "…and N more"
+const DIGEST_LINES: usize = 5;
+
+/// What happens to messages below the priority threshold during quiet hours
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum QuietAction {
+    /// No toast; queued for the digest
+    #[default]
+    Suppress,
+    /// Toast without sound, never persistent
+    Downgrade,
+}
+
+/// A recurring quiet period. When `end` is before `start` the period runs past
+/// midnight, and `days` refers to the day it starts on.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct QuietSchedule {
+    /// Weekdays as "mon".."sun"
+    pub days: Vec<String>,
+    /// "HH:MM"
+    pub start: String,
+    /// "HH:MM"
+    pub end: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct QuietHours {
+    pub enabled: bool,
+    /// IANA timezone such as "Europe/Berlin"; empty uses the system timezone
+    pub timezone: String,
+    pub schedules: Vec<QuietSchedule>,
+    /// Messages at or above this priority are shown normally
+    pub min_priority: u8,
+    pub action: QuietAction,
+    /// Show a summary of suppressed messages when quiet hours end
+    pub digest: bool,
+}
+
+impl Default for QuietHours {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            timezone: String::new(),
+            schedules: vec![QuietSchedule {
+                days: ["mon", "tue", "wed", "thu", "fri", "sat", "sun"].iter().map(|d| d.to_string()).collect(),
+                start: "22:00".to_string(),
+                end: "07:00".to_string(),
+            }],
+            min_priority: 4,
+            action: QuietAction::Suppress,
+            digest: true,
+        }
+    }
+}
+
+/// How the notification step treats a message
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum QuietDecision {
+    Show,
+    Downgrade,
+    Suppress,
+}
+
+fn parse_time(value: &str) -> Option<NaiveTime> {
+    NaiveTime::parse_from_str(value.trim(), "%H:%M").ok()
+}
+
+impl QuietSchedule {
+    fn has_day(&self, day: Weekday) -> bool {
+        self.days.iter().any(|d| d.parse::<Weekday>().ok() == Some(day))
+    }
+
+    /// Whether the schedule covers a local weekday and time
+    fn covers(&self, day: Weekday, time: NaiveTime) -> bool {
+        let (Some(start), Some(end)) = (parse_time(&self.start), parse_time(&self.end)) else {
+            return false;
+        };
+        if start == end {
+            // Whole day
+            self.has_day(day)
+        } else if start < end {
+            self.has_day(day) && time >= start && time < end
+        } else {
+            (self.has_day(day) && time >= start) || (self.has_day(day.pred()) && time < end)
+        }
+    }
+}
+
+impl QuietHours {
+    /// Check the configured timezone; empty means system local time
+    pub fn validate(&self) -> Result<()> {
+        if !self.timezone.is_empty() {
+            self.timezone
+                .parse::<Tz>()
+                .map_err(|_| anyhow::anyhow!("Unknown timezone '{}'", self.timezone))?;
+        }
+        for schedule in &self.schedules {
+            if parse_time(&schedule.start).is_none() || parse_time(&schedule.end).is_none() {
+                return Err(anyhow::anyhow!(
+                    "Invalid quiet hours time '{}'-'{}' (expected HH:MM)",
+                    schedule.start,
+                    schedule.end
+                ));
+            }
+            if let Some(day) = schedule.days.iter().find(|d| d.parse::<Weekday>().is_err()) {
+                return Err(anyhow::anyhow!("Invalid weekday '{}'", day));
+            }
+        }
+        Ok(())
+    }
+
+    /// Local weekday and time in the configured timezone
+    fn local(&self, now: DateTime<Utc>) -> (Weekday, NaiveTime) {
+        let naive = match self.timezone.parse::<Tz>() {
+            Ok(tz) if !self.timezone.is_empty() => tz.from_utc_datetime(&now.naive_utc()).naive_local(),
+            _ => now.with_timezone(&chrono::Local).naive_local(),
+        };
+        let time = NaiveTime::from_hms_opt(naive.hour(), naive.minute(), naive.second()).unwrap_or_default();
+        (naive.weekday(), time)
+    }
+
+    /// Whether quiet hours are in effect at `now`
+    pub fn is_active(&self, now: DateTime<Utc>) -> bool {
+        if !self.enabled {
+            return false;
+        }
+        let (day, time) = self.local(now);
+        self.schedules.iter().any(|s| s.covers(day, time))
+    }
+
+    /// Decide how to notify for a message of the given priority
+    pub fn decide(&self, priority: u8, now: DateTime<Utc>) -> QuietDecision {
+        if priority >= self.min_priority || !self.is_active(now) {
+            return QuietDecision::Show;
+        }
+        match self.action {
+            QuietAction::Suppress => QuietDecision::Suppress,
+            QuietAction::Downgrade => QuietDecision::Downgrade,
+        }
+    }
+}
+
+/// A message held back during quiet hours
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DigestEntry {
+    pub topic: String,
+    pub title: String,
+    pub priority: u8,
+    pub time: u64,
+}
+
+/// Messages suppressed during quiet hours, persisted so the digest survives restarts
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct DigestQueue {
+    pub entries: Vec<DigestEntry>,
+}
+
+impl DigestQueue {
+    pub fn load(path: &Path) -> Self {
+        match std::fs::read_to_string(path) {
+            Ok(json) => serde_json::from_str(&json).unwrap_or_else(|e| {
+                eprintln!("Ignoring invalid quiet hours digest {}: {}", path.display(), e);
+                Self::default()
+            }),
+            Err(_) => Self::default(),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if self.entries.is_empty() {
+            if path.exists() {
+                std::fs::remove_file(path)?;
+            }
+            return Ok(());
+        }
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(path, serde_json::to_string(self)?)?;
+        Ok(())
+    }
+
+    /// Digest notification (title, body), or `None` when nothing was held back
+    pub fn summary(&self) -> Option<(String, String)> {
+        let count = self.entries.len();
+        if count == 0 {
+            return None;
+        }
+        let noun = if count == 1 { "message" } else { "messages" };
+        let title = format!("{} {} during quiet hours", count, noun);
+
+        let mut lines: Vec<String> = self
+            .entries
+            .iter()
+            .rev()
+            .take(DIGEST_LINES)
+            .map(|e| format!("{}: {}", e.topic, e.title))
+            .collect();
+        if count > DIGEST_LINES {
+            lines.push(format!("…and {} more", count - DIGEST_LINES));
+        }
+        Some((title, lines.join("\n")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quiet_hours(timezone: &str, start: &str, end: &str, days: &[&str]) -> QuietHours {
+        QuietHours {
+            enabled: true,
+            timezone: timezone.to_string(),
+            schedules: vec![QuietSchedule {
+                days: days.iter().map(|d| d.to_string()).collect(),
+                start: start.to_string(),
+                end: end.to_string(),
+            }],
+            ..Default::default()
+        }
+    }
+
+    fn utc(y: i32, m: u32, d: u32, h: u32, min: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(y, m, d, h, min, 0).unwrap()
+    }
+
+    #[test]
+    fn test_overnight_schedule_in_timezone() {
+        // 2026-10-16 is a Friday; Berlin is UTC+2 (CEST) on these dates
+        let quiet = quiet_hours("Europe/Berlin", "22:00", "07:00", &["fri"]);
+
+        assert!(!quiet.is_active(utc(2026, 10, 16, 19, 59)));
+        assert!(quiet.is_active(utc(2026, 10, 16, 20, 0)));
+        // Saturday 06:59 local still belongs to Friday night
+        assert!(quiet.is_active(utc(2026, 10, 17, 4, 59)));
+        assert!(!quiet.is_active(utc(2026, 10, 17, 5, 0)));
+        // Saturday night is not scheduled
+        assert!(!quiet.is_active(utc(2026, 10, 17, 21, 0)));
+    }
+
+    #[test]
+    fn test_daytime_and_whole_day_schedules() {
+        let quiet = quiet_hours("UTC", "12:00", "13:00", &["monday"]);
+        assert!(quiet.is_active(utc(2026, 10, 19, 12, 30)));
+        assert!(!quiet.is_active(utc(2026, 10, 19, 13, 0)));
+        assert!(!quiet.is_active(utc(2026, 10, 20, 12, 30)));
+
+        let weekend = quiet_hours("UTC", "00:00", "00:00", &["sat", "sun"]);
+        assert!(weekend.is_active(utc(2026, 10, 18, 15, 0)));
+        assert!(!weekend.is_active(utc(2026, 10, 19, 15, 0)));
+    }
+
+    #[test]
+    fn test_decisions() {
+        let mut quiet = quiet_hours("UTC", "22:00", "07:00", &["sun"]);
+        let night = utc(2026, 10, 18, 23, 0);
+        let day = utc(2026, 10, 18, 12, 0);
+
+        assert_eq!(quiet.decide(3, night), QuietDecision::Suppress);
+        assert_eq!(quiet.decide(4, night), QuietDecision::Show);
+        assert_eq!(quiet.decide(3, day), QuietDecision::Show);
+
+        quiet.action = QuietAction::Downgrade;
+        assert_eq!(quiet.decide(1, night), QuietDecision::Downgrade);
+
+        quiet.enabled = false;
+        assert_eq!(quiet.decide(1, night), QuietDecision::Show);
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(QuietHours::default().validate().is_ok());
+        assert!(quiet_hours("Mars/Olympus", "22:00", "07:00", &["mon"]).validate().is_err());
+        assert!(quiet_hours("", "25:00", "07:00", &["mon"]).validate().is_err());
+        assert!(quiet_hours("", "22:00", "07:00", &["someday"]).validate().is_err());
+    }
+
+    #[test]
+    fn test_digest_queue() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("digest.json");
+
+        let mut queue = DigestQueue::load(&path);
+        assert_eq!(queue.summary(), None);
+
+        for i in 0..7 {
+            queue.entries.push(DigestEntry { topic: "ci".to_string(), title: format!("Build {}", i), priority: 2, time: i });
+        }
+        queue.save(&path).unwrap();
+
+        let loaded = DigestQueue::load(&path);
+        assert_eq!(loaded, queue);
+        let (title, body) = loaded.summary().unwrap();
+        assert_eq!(title, "7 messages during quiet hours");
+        assert_eq!(body.lines().next(), Some("ci: Build 6"));
+        assert_eq!(body.lines().last(), Some("…and 2 more"));
+
+        DigestQueue::default().save(&path).unwrap();
+        assert!(!path.exists());
+    }
+}
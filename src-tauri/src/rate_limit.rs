@@ -0,0 +1,183 @@
+//! Toast rate limits: token buckets per topic and across all topics. Throttled
+//! messages are still stored and counted; a topic that runs dry is muted for a
+//! while and announced once.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct RateLimits {
+    pub enabled: bool,
+    /// Toasts per minute a single topic refills
+    pub topic_per_minute: u32,
+    /// Toasts a single topic may show in a row
+    pub topic_burst: u32,
+    pub global_per_minute: u32,
+    pub global_burst: u32,
+    /// How long a flooding topic is muted
+    pub mute_minutes: u64,
+}
+
+impl Default for RateLimits {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            topic_per_minute: 20,
+            topic_burst: 30,
+            global_per_minute: 60,
+            global_burst: 60,
+            mute_minutes: 10,
+        }
+    }
+}
+
+/// How the notification step treats a message
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RateDecision {
+    Allow,
+    /// No toast, the message is only stored
+    Throttled,
+    /// The topic just ran out of tokens: mute it and announce it
+    Flooding,
+}
+
+#[derive(Debug, Clone)]
+struct TokenBucket {
+    tokens: f64,
+    /// Last refill (unix milliseconds)
+    updated: u64,
+}
+
+impl TokenBucket {
+    fn full(capacity: u32, now: u64) -> Self {
+        Self { tokens: capacity as f64, updated: now }
+    }
+
+    fn refill(&mut self, capacity: u32, per_minute: u32, now: u64) {
+        let elapsed = now.saturating_sub(self.updated) as f64;
+        self.tokens = (self.tokens + elapsed * per_minute as f64 / 60_000.0).min(capacity as f64);
+        self.updated = now;
+    }
+
+    fn has_token(&self) -> bool {
+        self.tokens >= 1.0
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct RateLimiter {
+    global: Option<TokenBucket>,
+    topics: HashMap<String, TokenBucket>,
+    /// End of the current flood per topic (unix milliseconds), so it is announced once
+    flooding: HashMap<String, u64>,
+}
+
+impl RateLimiter {
+    /// Take a token for a toast on `topic` at `now` (unix milliseconds)
+    pub fn check(&mut self, limits: &RateLimits, topic: &str, now: u64) -> RateDecision {
+        if !limits.enabled {
+            return RateDecision::Allow;
+        }
+        let global = self
+            .global
+            .get_or_insert_with(|| TokenBucket::full(limits.global_burst, now));
+        global.refill(limits.global_burst, limits.global_per_minute, now);
+        let bucket = self
+            .topics
+            .entry(topic.to_string())
+            .or_insert_with(|| TokenBucket::full(limits.topic_burst, now));
+        bucket.refill(limits.topic_burst, limits.topic_per_minute, now);
+
+        if !bucket.has_token() {
+            if self.flooding.get(topic).map_or(false, |&until| now < until) {
+                return RateDecision::Throttled;
+            }
+            self.flooding.insert(topic.to_string(), now + limits.mute_minutes * 60_000);
+            return RateDecision::Flooding;
+        }
+        if !global.has_token() {
+            return RateDecision::Throttled;
+        }
+        bucket.tokens -= 1.0;
+        global.tokens -= 1.0;
+        RateDecision::Allow
+    }
+}
+
+/// Notice shown when a topic starts flooding (title, body)
+pub fn flood_notice(topic_label: &str, mute_minutes: u64) -> (String, String) {
+    (
+        format!("{} is flooding", topic_label),
+        format!(
+            "Topic {} is flooding, muted for {} minutes. New messages are still stored in history.",
+            topic_label, mute_minutes
+        ),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limits() -> RateLimits {
+        RateLimits {
+            enabled: true,
+            topic_per_minute: 6,
+            topic_burst: 3,
+            global_per_minute: 60,
+            global_burst: 5,
+            mute_minutes: 10,
+        }
+    }
+
+    #[test]
+    fn test_topic_bucket() {
+        let mut limiter = RateLimiter::default();
+        let decisions: Vec<RateDecision> = (0..5).map(|_| limiter.check(&limits(), "cron", 0)).collect();
+        assert_eq!(
+            decisions,
+            vec![
+                RateDecision::Allow,
+                RateDecision::Allow,
+                RateDecision::Allow,
+                RateDecision::Flooding,
+                RateDecision::Throttled,
+            ]
+        );
+
+        // 6 per minute refills one token every 10 seconds
+        assert_eq!(limiter.check(&limits(), "cron", 9_000), RateDecision::Throttled);
+        assert_eq!(limiter.check(&limits(), "cron", 10_000), RateDecision::Allow);
+
+        // A new flood after the mute period is announced again
+        assert_eq!(limiter.check(&limits(), "cron", 10_000), RateDecision::Throttled);
+        assert_eq!(limiter.check(&limits(), "cron", 600_000), RateDecision::Allow);
+        for _ in 0..2 {
+            limiter.check(&limits(), "cron", 600_000);
+        }
+        assert_eq!(limiter.check(&limits(), "cron", 600_000), RateDecision::Flooding);
+    }
+
+    #[test]
+    fn test_global_bucket() {
+        let mut limiter = RateLimiter::default();
+        let allowed = ["a", "a", "b", "b", "c", "c", "d"]
+            .iter()
+            .filter(|topic| limiter.check(&limits(), topic, 0) == RateDecision::Allow)
+            .count();
+        assert_eq!(allowed, 5);
+        // Running out of global tokens throttles without blaming a topic
+        assert_eq!(limiter.check(&limits(), "d", 0), RateDecision::Throttled);
+
+        let disabled = RateLimits { enabled: false, ..limits() };
+        assert!((0..100).all(|_| limiter.check(&disabled, "a", 0) == RateDecision::Allow));
+    }
+
+    #[test]
+    fn test_flood_notice() {
+        let (title, body) = flood_notice("ci-alerts", 10);
+        assert_eq!(title, "ci-alerts is flooding");
+        assert!(body.starts_with("Topic ci-alerts is flooding, muted for 10 minutes."));
+    }
+}
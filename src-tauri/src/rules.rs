@@ -0,0 +1,294 @@
+//! User-defined rules, evaluated in order for every incoming message before
+//! its notification is built.
+
+use anyhow::Result;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use super::config::NotificationSound;
+use super::hooks::HookCommand;
+use super::ntfy::NtfyMessage;
+
+/// What a rule matches. Empty conditions match every message.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct RuleConditions {
+    /// Any of these topics (empty = all topics)
+    pub topics: Vec<String>,
+    /// Regex matched against the title
+    pub title: Option<String>,
+    /// Regex matched against the message body
+    pub message: Option<String>,
+    /// All of these tags must be present
+    pub tags: Vec<String>,
+    pub min_priority: Option<u8>,
+    pub max_priority: Option<u8>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RuleAction {
+    /// Store the message but show no notification
+    Suppress,
+    SetPriority { priority: u8 },
+    SetUrgent { urgent: bool },
+    SetSound { sound: NotificationSound },
+    /// Keep the notification until dismissed
+    Persist,
+    /// New title; `{title}`, `{topic}`, `{message}` and `{priority}` are replaced
+    SetTitle { title: String },
+    /// Run a command for the message (see `hooks`)
+    RunCommand {
+        command: String,
+        #[serde(default)]
+        timeout_secs: Option<u64>,
+    },
+    /// Skip the remaining rules
+    Stop,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Rule {
+    pub name: String,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    #[serde(default)]
+    pub conditions: RuleConditions,
+    pub actions: Vec<RuleAction>,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// Combined effect of all matching rules on one message
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+pub struct RuleOutcome {
+    /// Names of the rules that matched, in evaluation order
+    pub matched: Vec<String>,
+    pub suppress: bool,
+    pub priority: Option<u8>,
+    pub urgent: Option<bool>,
+    pub sound: Option<NotificationSound>,
+    pub persistent: bool,
+    pub title: Option<String>,
+    /// Commands to run, in rule order
+    pub hooks: Vec<HookCommand>,
+}
+
+struct CompiledRule {
+    rule: Rule,
+    title: Option<Regex>,
+    message: Option<Regex>,
+}
+
+/// Rules with their regexes compiled, ready to evaluate
+#[derive(Default)]
+pub struct RuleSet {
+    rules: Vec<CompiledRule>,
+}
+
+fn compile_regex(rule: &Rule, field: &str, pattern: &Option<String>) -> Result<Option<Regex>> {
+    match pattern.as_deref().filter(|p| !p.is_empty()) {
+        Some(pattern) => Regex::new(pattern)
+            .map(Some)
+            .map_err(|e| anyhow::anyhow!("Rule '{}': invalid {} pattern: {}", rule.name, field, e)),
+        None => Ok(None),
+    }
+}
+
+impl RuleSet {
+    /// Compile enabled rules; fails on the first invalid regex
+    pub fn compile(rules: &[Rule]) -> Result<Self> {
+        let mut compiled = Vec::new();
+        for rule in rules.iter().filter(|r| r.enabled) {
+            compiled.push(CompiledRule {
+                title: compile_regex(rule, "title", &rule.conditions.title)?,
+                message: compile_regex(rule, "message", &rule.conditions.message)?,
+                rule: rule.clone(),
+            });
+        }
+        Ok(Self { rules: compiled })
+    }
+
+    /// Apply matching rules in order. Later rules see the priority set by earlier ones.
+    pub fn evaluate(&self, msg: &NtfyMessage) -> RuleOutcome {
+        let mut outcome = RuleOutcome::default();
+        for compiled in &self.rules {
+            let priority = outcome.priority.or(msg.priority).unwrap_or(3);
+            if !compiled.matches(msg, priority) {
+                continue;
+            }
+            outcome.matched.push(compiled.rule.name.clone());
+
+            for action in &compiled.rule.actions {
+                match action {
+                    RuleAction::Suppress => outcome.suppress = true,
+                    RuleAction::SetPriority { priority } => outcome.priority = Some((*priority).clamp(1, 5)),
+                    RuleAction::SetUrgent { urgent } => outcome.urgent = Some(*urgent),
+                    RuleAction::SetSound { sound } => outcome.sound = Some(sound.clone()),
+                    RuleAction::Persist => outcome.persistent = true,
+                    RuleAction::SetTitle { title } => {
+                        let current = outcome.title.clone().or_else(|| msg.title.clone());
+                        outcome.title = Some(render_title(title, msg, current.as_deref(), priority));
+                    }
+                    RuleAction::RunCommand { command, timeout_secs } => outcome.hooks.push(HookCommand {
+                        command: command.clone(),
+                        timeout_secs: *timeout_secs,
+                    }),
+                    RuleAction::Stop => return outcome,
+                }
+            }
+        }
+        outcome
+    }
+}
+
+impl CompiledRule {
+    fn matches(&self, msg: &NtfyMessage, priority: u8) -> bool {
+        let conditions = &self.rule.conditions;
+        let topic = msg.topic.as_deref().unwrap_or_default();
+        if !conditions.topics.is_empty() && !conditions.topics.iter().any(|t| t == topic) {
+            return false;
+        }
+        if let Some(regex) = &self.title {
+            if !regex.is_match(msg.title.as_deref().unwrap_or_default()) {
+                return false;
+            }
+        }
+        if let Some(regex) = &self.message {
+            if !regex.is_match(msg.message.as_deref().unwrap_or_default()) {
+                return false;
+            }
+        }
+        let tags = msg.tags.as_deref().unwrap_or_default();
+        if !conditions.tags.iter().all(|wanted| tags.iter().any(|t| t.eq_ignore_ascii_case(wanted))) {
+            return false;
+        }
+        conditions.min_priority.map_or(true, |min| priority >= min)
+            && conditions.max_priority.map_or(true, |max| priority <= max)
+    }
+}
+
+fn render_title(template: &str, msg: &NtfyMessage, title: Option<&str>, priority: u8) -> String {
+    template
+        .replace("{title}", title.unwrap_or_default())
+        .replace("{topic}", msg.topic.as_deref().unwrap_or_default())
+        .replace("{message}", msg.message.as_deref().unwrap_or_default())
+        .replace("{priority}", &priority.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(topic: &str, title: &str, body: &str, priority: u8, tags: &[&str]) -> NtfyMessage {
+        serde_json::from_value(serde_json::json!({
+            "id": "m1",
+            "time": 1,
+            "topic": topic,
+            "title": title,
+            "message": body,
+            "priority": priority,
+            "tags": tags,
+        }))
+        .unwrap()
+    }
+
+    fn rule(name: &str, conditions: RuleConditions, actions: Vec<RuleAction>) -> Rule {
+        Rule { name: name.to_string(), enabled: true, conditions, actions }
+    }
+
+    #[test]
+    fn test_conditions() {
+        let rules = RuleSet::compile(&[rule(
+            "ci failures",
+            RuleConditions {
+                topics: vec!["ci".to_string()],
+                title: Some("(?i)failed".to_string()),
+                tags: vec!["build".to_string()],
+                min_priority: Some(3),
+                max_priority: Some(4),
+                ..Default::default()
+            },
+            vec![RuleAction::Persist],
+        )])
+        .unwrap();
+
+        assert!(rules.evaluate(&message("ci", "Build FAILED", "", 3, &["Build"])).persistent);
+        assert!(!rules.evaluate(&message("deploys", "Build failed", "", 3, &["build"])).persistent);
+        assert!(!rules.evaluate(&message("ci", "Build passed", "", 3, &["build"])).persistent);
+        assert!(!rules.evaluate(&message("ci", "Build failed", "", 3, &[])).persistent);
+        assert!(!rules.evaluate(&message("ci", "Build failed", "", 5, &["build"])).persistent);
+    }
+
+    #[test]
+    fn test_actions_in_order_with_stop() {
+        let rules = RuleSet::compile(&[
+            rule(
+                "raise",
+                RuleConditions { message: Some("disk".to_string()), ..Default::default() },
+                vec![
+                    RuleAction::SetPriority { priority: 5 },
+                    RuleAction::SetSound { sound: NotificationSound::Alert },
+                    RuleAction::SetTitle { title: "[{topic}] {title} (p{priority})".to_string() },
+                ],
+            ),
+            // Sees the raised priority
+            rule(
+                "urgent only",
+                RuleConditions { min_priority: Some(5), ..Default::default() },
+                vec![RuleAction::SetUrgent { urgent: true }, RuleAction::Stop, RuleAction::Suppress],
+            ),
+            rule("never reached", RuleConditions::default(), vec![RuleAction::Suppress]),
+        ])
+        .unwrap();
+
+        let outcome = rules.evaluate(&message("ops", "Host down", "disk full", 2, &[]));
+        assert_eq!(outcome.matched, vec!["raise", "urgent only"]);
+        assert_eq!(outcome.priority, Some(5));
+        assert_eq!(outcome.urgent, Some(true));
+        assert_eq!(outcome.sound, Some(NotificationSound::Alert));
+        assert_eq!(outcome.title.as_deref(), Some("[ops] Host down (p2)"));
+        assert!(!outcome.suppress);
+
+        let outcome = rules.evaluate(&message("ops", "Heartbeat", "ok", 3, &[]));
+        assert_eq!(outcome.matched, vec!["never reached"]);
+        assert!(outcome.suppress);
+    }
+
+    #[test]
+    fn test_disabled_and_invalid_rules() {
+        let mut disabled = rule("off", RuleConditions::default(), vec![RuleAction::Suppress]);
+        disabled.enabled = false;
+        let rules = RuleSet::compile(&[disabled]).unwrap();
+        assert_eq!(rules.evaluate(&message("a", "", "", 3, &[])), RuleOutcome::default());
+
+        let invalid = rule(
+            "broken",
+            RuleConditions { title: Some("(".to_string()), ..Default::default() },
+            vec![RuleAction::Suppress],
+        );
+        let error = RuleSet::compile(&[invalid]).err().unwrap().to_string();
+        assert!(error.contains("Rule 'broken'"));
+    }
+
+    #[test]
+    fn test_rule_json_format() {
+        let json = r#"{
+            "name": "quiet heartbeats",
+            "conditions": { "topics": ["monitoring"], "max_priority": 2 },
+            "actions": [{ "type": "suppress" }, { "type": "set_sound", "sound": "none" }, { "type": "stop" }]
+        }"#;
+        let rule: Rule = serde_json::from_str(json).unwrap();
+        assert!(rule.enabled);
+        assert_eq!(rule.conditions.max_priority, Some(2));
+        assert_eq!(rule.actions[1], RuleAction::SetSound { sound: NotificationSound::None });
+
+        let action: RuleAction = serde_json::from_str(r#"{ "type": "run_command", "command": "notify-light red" }"#).unwrap();
+        let light = Rule { name: "light".to_string(), enabled: true, conditions: RuleConditions::default(), actions: vec![action] };
+        let rules = RuleSet::compile(&[light]).unwrap();
+        let outcome = rules.evaluate(&message("ops", "", "", 3, &[]));
+        assert_eq!(outcome.hooks, vec![HookCommand { command: "notify-light red".to_string(), timeout_secs: None }]);
+    }
+}
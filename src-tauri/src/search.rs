@@ -0,0 +1,419 @@
+//! Search over locally received messages.
+//!
+//! Query syntax: free-text words (or "quoted phrases") match title or body;
+//! structured filters narrow the results:
+//!
+//! - `prio>=4`, `prio<3`, `prio:5`, `prio:2-4`, `prio:high` (also `priority`/`p`);
+//!   bounds past 1-5 (`prio>5`) match nothing, non-numeric values (`p=np`) are text
+//! - `tag:warning` (every tag must be present)
+//! - `topic:alerts` (any of the given topics)
+//! - `server:ntfy.sh` (substring of the server URL)
+//! - `since:2026-10-01`, `since:7d`, `before:2026-10-02`, `until:12h` (`after` = `since`)
+
+use anyhow::Result;
+use chrono::{Local, NaiveDate, NaiveDateTime, TimeZone};
+use serde::Serialize;
+
+use super::history::{HistoryEntry, HistoryStore};
+
+/// Parsed search query
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SearchQuery {
+    pub terms: Vec<String>,
+    pub topics: Vec<String>,
+    pub servers: Vec<String>,
+    pub tags: Vec<String>,
+    pub min_priority: Option<u8>,
+    pub max_priority: Option<u8>,
+    /// Inclusive lower bound on message time (unix seconds)
+    pub since: Option<u64>,
+    /// Exclusive upper bound on message time (unix seconds)
+    pub until: Option<u64>,
+}
+
+/// Matched range inside a field, in UTF-16 code units so the web UI can slice directly
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct Highlight {
+    pub field: &'static str,
+    pub start: usize,
+    pub end: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchHit {
+    #[serde(flatten)]
+    pub entry: HistoryEntry,
+    pub highlights: Vec<Highlight>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchPage {
+    pub total: u64,
+    pub offset: u64,
+    pub hits: Vec<SearchHit>,
+}
+
+/// Split the input into words, keeping "quoted phrases" (and `key:"quoted value"`) together
+fn tokenize(input: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+
+    for c in input.chars() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            c if c.is_whitespace() && !in_quotes => {
+                if !current.is_empty() {
+                    tokens.push(std::mem::take(&mut current));
+                }
+            }
+            c => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+    tokens
+}
+
+/// Parse 1-5 or an ntfy priority name. `None` means the value isn't a priority at all
+/// and the token is searched for as text; out-of-range numbers are an error.
+fn parse_priority(value: &str) -> Result<Option<u8>> {
+    let priority = match value.to_lowercase().as_str() {
+        "min" => 1,
+        "low" => 2,
+        "default" => 3,
+        "high" => 4,
+        "max" | "urgent" => 5,
+        v if !v.is_empty() && v.bytes().all(|b| b.is_ascii_digit()) => v
+            .parse::<u8>()
+            .ok()
+            .filter(|p| (1..=5).contains(p))
+            .ok_or_else(|| anyhow::anyhow!("Invalid priority '{}' (expected 1-5 or min, low, default, high, max)", value))?,
+        _ => return Ok(None),
+    };
+    Ok(Some(priority))
+}
+
+/// Parse `7d`, `12h`, `30m`, `2w` relative to now, or a local `YYYY-MM-DD[THH:MM]` timestamp
+fn parse_time(value: &str, now: u64) -> Result<u64> {
+    let invalid = || anyhow::anyhow!("Invalid time '{}' (use e.g. 7d, 12h or 2026-10-01)", value);
+
+    if let Some(unit) = value.chars().last().filter(|c| c.is_ascii_alphabetic()) {
+        let amount: u64 = value[..value.len() - 1].parse().map_err(|_| invalid())?;
+        let seconds: u64 = match unit {
+            'm' => 60,
+            'h' => 3600,
+            'd' => 86400,
+            'w' => 7 * 86400,
+            _ => return Err(invalid()),
+        };
+        let offset = amount.checked_mul(seconds).ok_or_else(invalid)?;
+        return Ok(now.saturating_sub(offset));
+    }
+
+    let naive = NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M")
+        .or_else(|_| NaiveDate::parse_from_str(value, "%Y-%m-%d").map(|d| d.and_hms_opt(0, 0, 0).unwrap_or_default()))
+        .map_err(|_| invalid())?;
+    let local = Local.from_local_datetime(&naive).earliest().ok_or_else(invalid)?;
+    Ok(local.timestamp().max(0) as u64)
+}
+
+impl SearchQuery {
+    /// Parse a query string; `now` anchors relative times like `7d`
+    pub fn parse(input: &str, now: u64) -> Result<Self> {
+        let mut query = SearchQuery::default();
+
+        for token in tokenize(input) {
+            let lower = token.to_lowercase();
+
+            // Priority comparisons: prio>=4, p<3, priority=5
+            let comparison = ["priority", "prio", "p"].iter().find_map(|key| {
+                let rest = lower.strip_prefix(key)?;
+                [">=", "<=", ">", "<", "="]
+                    .iter()
+                    .find_map(|op| rest.strip_prefix(op).map(|value| (*op, value.to_string())))
+            });
+            let comparison = match comparison {
+                Some((op, value)) => parse_priority(&value)?.map(|priority| (op, priority)),
+                None => None,
+            };
+            if let Some((op, priority)) = comparison {
+                // prio>5 and prio<1 leave bounds of 6 and 0, which no message has
+                match op {
+                    ">=" => query.min_priority = Some(priority),
+                    "<=" => query.max_priority = Some(priority),
+                    ">" => query.min_priority = Some(priority + 1),
+                    "<" => query.max_priority = Some(priority - 1),
+                    _ => {
+                        query.min_priority = Some(priority);
+                        query.max_priority = Some(priority);
+                    }
+                }
+                continue;
+            }
+
+            let Some((key, value)) = token.split_once(':').filter(|(_, v)| !v.is_empty()) else {
+                query.terms.push(token);
+                continue;
+            };
+
+            match key.to_lowercase().as_str() {
+                "priority" | "prio" | "p" => {
+                    let (min, max) = match value.split_once('-') {
+                        Some((min, max)) => (parse_priority(min)?, parse_priority(max)?),
+                        None => {
+                            let priority = parse_priority(value)?;
+                            (priority, priority)
+                        }
+                    };
+                    match (min, max) {
+                        (Some(min), Some(max)) => {
+                            query.min_priority = Some(min);
+                            query.max_priority = Some(max);
+                        }
+                        _ => query.terms.push(token),
+                    }
+                }
+                "tag" | "tags" => query.tags.extend(value.split(',').filter(|t| !t.is_empty()).map(String::from)),
+                "topic" => query.topics.extend(value.split(',').filter(|t| !t.is_empty()).map(String::from)),
+                "server" => query.servers.push(value.to_string()),
+                "since" | "after" => query.since = Some(parse_time(value, now)?),
+                "before" | "until" => query.until = Some(parse_time(value, now)?),
+                // Not a filter (e.g. "error: disk full"), search for it literally
+                _ => query.terms.push(token),
+            }
+        }
+
+        Ok(query)
+    }
+}
+
+/// Case-insensitive matches of `term` in `text`, as UTF-16 ranges
+fn find_matches(text: &str, term: &str) -> Vec<(usize, usize)> {
+    let needle: Vec<char> = term.chars().flat_map(char::to_lowercase).collect();
+    let haystack: Vec<char> = text.chars().collect();
+    if needle.is_empty() {
+        return Vec::new();
+    }
+
+    let mut ranges = Vec::new();
+    let mut start = 0;
+    while start < haystack.len() {
+        // Compare char by char, expanding each haystack char's lowercase form
+        let mut matched = 0;
+        let mut end = start;
+        while end < haystack.len() && matched < needle.len() {
+            let lowered: Vec<char> = haystack[end].to_lowercase().collect();
+            if needle[matched..].starts_with(&lowered) {
+                matched += lowered.len();
+                end += 1;
+            } else {
+                break;
+            }
+        }
+
+        if matched == needle.len() {
+            let utf16_start: usize = haystack[..start].iter().map(|c| c.len_utf16()).sum();
+            let utf16_len: usize = haystack[start..end].iter().map(|c| c.len_utf16()).sum();
+            ranges.push((utf16_start, utf16_start + utf16_len));
+            start = end;
+        } else {
+            start += 1;
+        }
+    }
+    ranges
+}
+
+/// Highlight every occurrence of the query's free-text terms in title and body
+pub fn highlights(entry: &HistoryEntry, query: &SearchQuery) -> Vec<Highlight> {
+    let mut highlights = Vec::new();
+    for (field, text) in [("title", &entry.title), ("message", &entry.message)] {
+        let Some(text) = text else { continue };
+        let mut ranges: Vec<(usize, usize)> = query.terms.iter().flat_map(|term| find_matches(text, term)).collect();
+        ranges.sort_unstable();
+
+        // Merge overlapping matches of different terms
+        let mut merged: Vec<(usize, usize)> = Vec::new();
+        for (start, end) in ranges {
+            match merged.last_mut() {
+                Some(last) if start <= last.1 => last.1 = last.1.max(end),
+                _ => merged.push((start, end)),
+            }
+        }
+        highlights.extend(merged.into_iter().map(|(start, end)| Highlight { field, start, end }));
+    }
+    highlights
+}
+
+/// Run a query string against the history store
+pub fn search(store: &HistoryStore, input: &str, offset: u64, limit: u64) -> Result<SearchPage> {
+    let now = chrono::Utc::now().timestamp().max(0) as u64;
+    let query = SearchQuery::parse(input, now)?;
+    let (total, entries) = store.search(&query, offset, limit)?;
+
+    let hits = entries
+        .into_iter()
+        .map(|entry| SearchHit { highlights: highlights(&entry, &query), entry })
+        .collect();
+
+    Ok(SearchPage { total, offset, hits })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ntfy::NtfyMessage;
+
+    const NOW: u64 = 1_800_000_000;
+
+    #[test]
+    fn test_parse_filters_and_terms() {
+        let query = SearchQuery::parse("prio>=4 tag:warning db \"disk full\" topic:alerts,ops server:ntfy.sh", NOW).unwrap();
+
+        assert_eq!(query.min_priority, Some(4));
+        assert_eq!(query.max_priority, None);
+        assert_eq!(query.tags, vec!["warning"]);
+        assert_eq!(query.terms, vec!["db", "disk full"]);
+        assert_eq!(query.topics, vec!["alerts", "ops"]);
+        assert_eq!(query.servers, vec!["ntfy.sh"]);
+    }
+
+    #[test]
+    fn test_parse_priority_forms() {
+        let parse = |q: &str| {
+            let query = SearchQuery::parse(q, NOW).unwrap();
+            (query.min_priority, query.max_priority)
+        };
+
+        assert_eq!(parse("prio:5"), (Some(5), Some(5)));
+        assert_eq!(parse("p=3"), (Some(3), Some(3)));
+        assert_eq!(parse("priority:2-4"), (Some(2), Some(4)));
+        assert_eq!(parse("prio>3"), (Some(4), None));
+        assert_eq!(parse("prio<3"), (None, Some(2)));
+        assert_eq!(parse("prio<=2 prio>=1"), (Some(1), Some(2)));
+
+        assert_eq!(parse("prio:high"), (Some(4), Some(4)));
+        assert_eq!(parse("p>=urgent"), (Some(5), None));
+
+        assert!(SearchQuery::parse("prio>=9", NOW).is_err());
+        assert!(SearchQuery::parse("prio:0-3", NOW).is_err());
+    }
+
+    #[test]
+    fn test_parse_priority_edge_cases() {
+        // Bounds past the ends of the range match nothing instead of priority 5 or 1
+        let query = SearchQuery::parse("prio>5", NOW).unwrap();
+        assert_eq!(query.min_priority, Some(6));
+        let query = SearchQuery::parse("prio<1", NOW).unwrap();
+        assert_eq!(query.max_priority, Some(0));
+
+        // Things that only look like priority filters are searched for as text
+        let query = SearchQuery::parse("p=np prio:unknown p>x", NOW).unwrap();
+        assert_eq!(query.terms, vec!["p=np", "prio:unknown", "p>x"]);
+        assert_eq!((query.min_priority, query.max_priority), (None, None));
+    }
+
+    #[test]
+    fn test_parse_times() {
+        let query = SearchQuery::parse("since:7d until:12h", NOW).unwrap();
+        assert_eq!(query.since, Some(NOW - 7 * 86400));
+        assert_eq!(query.until, Some(NOW - 12 * 3600));
+
+        let query = SearchQuery::parse("after:2026-10-01 before:2026-10-02T08:30", NOW).unwrap();
+        let expected = Local.with_ymd_and_hms(2026, 10, 1, 0, 0, 0).unwrap().timestamp() as u64;
+        assert_eq!(query.since, Some(expected));
+        assert_eq!(query.until, Some(expected + 86400 + 8 * 3600 + 30 * 60));
+
+        assert!(SearchQuery::parse("since:yesterday", NOW).is_err());
+        assert!(SearchQuery::parse("since:5y", NOW).is_err());
+        // Overflowing offsets are rejected rather than panicking
+        let err = SearchQuery::parse("since:999999999999999999w", NOW).unwrap_err();
+        assert!(err.to_string().contains("Invalid time"));
+    }
+
+    #[test]
+    fn test_unknown_keys_are_text() {
+        let query = SearchQuery::parse("error: https://example.com", NOW).unwrap();
+        assert_eq!(query.terms, vec!["error:", "https://example.com"]);
+    }
+
+    #[test]
+    fn test_highlights() {
+        let entry = HistoryEntry {
+            id: "a".to_string(),
+            server: "https://ntfy.sh".to_string(),
+            topic: "alerts".to_string(),
+            time: NOW,
+            received_at: NOW,
+            priority: 4,
+            title: Some("Disk full on DB-1".to_string()),
+            message: Some("🔥 db disk usage 99%, disk full soon".to_string()),
+            tags: vec![],
+            read: false,
+            raw: "{}".to_string(),
+        };
+        let query = SearchQuery::parse("db \"disk full\"", NOW).unwrap();
+
+        assert_eq!(
+            highlights(&entry, &query),
+            vec![
+                Highlight { field: "title", start: 0, end: 9 },
+                Highlight { field: "title", start: 13, end: 15 },
+                // The emoji takes two UTF-16 code units
+                Highlight { field: "message", start: 3, end: 5 },
+                Highlight { field: "message", start: 22, end: 31 },
+            ]
+        );
+    }
+
+    fn message(id: &str, topic: &str, priority: u8, tags: &[&str], title: &str, time: u64) -> NtfyMessage {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "time": time,
+            "topic": topic,
+            "title": title,
+            "message": format!("body of {}", title),
+            "priority": priority,
+            "tags": tags,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_search_store() {
+        let store = HistoryStore::open_in_memory().unwrap();
+        let now = chrono::Utc::now().timestamp() as u64;
+        store.insert("https://ntfy.sh", &message("a", "alerts", 5, &["warning", "db"], "Disk full", now - 86400)).unwrap();
+        store.insert("https://ntfy.sh", &message("b", "alerts", 3, &["db"], "Backup done", now - 3600)).unwrap();
+        store.insert("https://ntfy.example.com", &message("c", "deploys", 4, &["warning"], "Deploy failed", now)).unwrap();
+        store.insert("https://ntfy.sh", &message("d", "alerts", 4, &["warning"], "100% match_me", now - 60)).unwrap();
+
+        let ids = |q: &str| -> Vec<String> {
+            search(&store, q, 0, 10).unwrap().hits.into_iter().map(|h| h.entry.id).collect()
+        };
+
+        assert_eq!(ids("prio>=4 tag:warning"), vec!["c", "d", "a"]);
+        assert_eq!(ids("prio>=4 tag:warning disk"), vec!["a"]);
+        assert_eq!(ids("tag:warning tag:db"), vec!["a"]);
+        assert_eq!(ids("topic:deploys"), vec!["c"]);
+        assert_eq!(ids("server:example"), vec!["c"]);
+        assert_eq!(ids("since:2h"), vec!["c", "d", "b"]);
+        assert_eq!(ids("before:2h"), vec!["a"]);
+        assert_eq!(ids("BACKUP"), vec!["b"]);
+        // LIKE wildcards in terms are matched literally
+        assert_eq!(ids("100%"), vec!["d"]);
+        assert_eq!(ids("h_me"), vec!["d"]);
+        assert!(ids("nothing-matches").is_empty());
+        assert!(ids("prio>5").is_empty());
+        assert!(ids("prio<1").is_empty());
+
+        let page = search(&store, "tag:warning", 1, 1).unwrap();
+        assert_eq!(page.total, 3);
+        assert_eq!(page.hits.len(), 1);
+        assert_eq!(page.hits[0].entry.id, "d");
+
+        let page = search(&store, "disk", 0, 10).unwrap();
+        assert_eq!(page.hits[0].highlights[0], Highlight { field: "title", start: 0, end: 4 });
+    }
+}
@@ -0,0 +1,92 @@
+//! Custom notification sounds: WAV, OGG Vorbis and FLAC files played by the
+//! app itself instead of a system sound.
+
+use anyhow::Result;
+use rodio::{Decoder, OutputStream, Sink, Source};
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+use std::sync::mpsc;
+
+fn open(path: &Path) -> Result<Decoder<BufReader<File>>> {
+    let file = File::open(path).map_err(|e| anyhow::anyhow!("Cannot open sound file {}: {}", path.display(), e))?;
+    Decoder::new(BufReader::new(file))
+        .map_err(|e| anyhow::anyhow!("Unsupported sound file {} (use WAV, OGG or FLAC): {}", path.display(), e))
+}
+
+/// Check that a sound file exists and decodes to audio
+pub fn validate(path: &Path) -> Result<()> {
+    let mut decoder = open(path)?;
+    if decoder.channels() == 0 || decoder.sample_rate() == 0 || decoder.next().is_none() {
+        return Err(anyhow::anyhow!("Sound file {} contains no audio", path.display()));
+    }
+    Ok(())
+}
+
+/// Start playing a sound file. Returns once playback started; it continues in
+/// the background.
+pub fn play(path: &Path) -> Result<()> {
+    let decoder = open(path)?;
+    let (started_tx, started_rx) = mpsc::channel();
+    // The output stream is tied to the thread that opened it
+    std::thread::spawn(move || {
+        let output = OutputStream::try_default()
+            .map_err(|e| anyhow::anyhow!("No audio output: {}", e))
+            .and_then(|(stream, handle)| {
+                let sink = Sink::try_new(&handle).map_err(|e| anyhow::anyhow!("No audio output: {}", e))?;
+                Ok((stream, sink))
+            });
+        match output {
+            Ok((_stream, sink)) => {
+                sink.append(decoder);
+                let _ = started_tx.send(Ok(()));
+                sink.sleep_until_end();
+            }
+            Err(e) => {
+                let _ = started_tx.send(Err(e));
+            }
+        }
+    });
+    started_rx
+        .recv()
+        .map_err(|_| anyhow::anyhow!("Sound playback stopped unexpectedly"))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_wav(path: &Path, samples: &[i16]) {
+        let spec = hound::WavSpec {
+            channels: 1,
+            sample_rate: 8000,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let mut writer = hound::WavWriter::create(path, spec).unwrap();
+        for sample in samples {
+            writer.write_sample(*sample).unwrap();
+        }
+        writer.finalize().unwrap();
+    }
+
+    #[test]
+    fn test_validate_sound_files() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let beep = dir.path().join("beep.wav");
+        let samples: Vec<i16> = (0..800).map(|i| ((i as f32 / 4.0).sin() * 8000.0) as i16).collect();
+        write_wav(&beep, &samples);
+        assert!(validate(&beep).is_ok());
+
+        let empty = dir.path().join("empty.wav");
+        write_wav(&empty, &[]);
+        assert!(validate(&empty).is_err());
+
+        let text = dir.path().join("notes.ogg");
+        std::fs::write(&text, "not audio").unwrap();
+        assert!(validate(&text).unwrap_err().to_string().contains("Unsupported sound file"));
+
+        assert!(validate(&dir.path().join("missing.flac")).is_err());
+    }
+}
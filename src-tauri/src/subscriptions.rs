@@ -0,0 +1,299 @@
+//! Topic subscriptions with per-topic notification settings. Unset fields
+//! fall back to the global values in `AppConfig`.
+
+use serde::{Deserialize, Deserializer, Serialize};
+
+use super::config::{NotificationSound, PersistentNotificationMode};
+use super::hooks::HookCommand;
+use super::priority::{PriorityPolicies, PriorityPolicy};
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct TopicSubscription {
+    pub topic: String,
+    /// Shown instead of the topic name in notifications and the tray menu
+    pub display_name: Option<String>,
+    /// Messages are stored but no toast is shown
+    pub muted: bool,
+    /// End of a temporary mute (unix seconds); `None` mutes until unmuted
+    pub muted_until: Option<u64>,
+    /// What each priority level does on this topic, instead of the global policies
+    pub priority_policies: Option<PriorityPolicies>,
+    // Deprecated: kept for migration into priority_policies (see AppConfig::migrate)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sound: Option<NotificationSound>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub urgent_sound: Option<NotificationSound>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub persistence: Option<PersistentNotificationMode>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub urgent_priority_threshold: Option<u8>,
+    /// Icon URL or file path used instead of the message icon
+    pub icon: Option<String>,
+    /// Command run for every message on this topic
+    pub hook: Option<HookCommand>,
+    /// Also write messages to the system journal (Linux)
+    pub journal: bool,
+    /// Open click URLs without asking
+    pub trusted: bool,
+}
+
+impl TopicSubscription {
+    pub fn new(topic: &str) -> Self {
+        Self { topic: topic.to_string(), ..Default::default() }
+    }
+
+    /// Display name, or the topic itself when none is set
+    pub fn label(&self) -> &str {
+        match self.display_name.as_deref() {
+            Some(name) if !name.trim().is_empty() => name,
+            _ => &self.topic,
+        }
+    }
+
+    /// Whether the topic is muted at `now` (unix seconds)
+    pub fn is_muted_at(&self, now: u64) -> bool {
+        self.muted && self.muted_until.map_or(true, |until| now < until)
+    }
+
+    /// Whether overrides from before per-priority policies are set
+    pub fn has_legacy_overrides(&self) -> bool {
+        self.sound.is_some()
+            || self.urgent_sound.is_some()
+            || self.persistence.is_some()
+            || self.urgent_priority_threshold.is_some()
+    }
+
+    /// Drop the overrides from before per-priority policies once migrated
+    pub fn clear_legacy_overrides(&mut self) {
+        self.sound = None;
+        self.urgent_sound = None;
+        self.persistence = None;
+        self.urgent_priority_threshold = None;
+    }
+}
+
+/// Parse a comma-separated topic list (the pre-subscription config format)
+pub fn parse_topics(topics: &str) -> Vec<TopicSubscription> {
+    let mut subscriptions: Vec<TopicSubscription> = Vec::new();
+    for topic in topics.split(',').map(str::trim).filter(|t| !t.is_empty()) {
+        if !subscriptions.iter().any(|s| s.topic == topic) {
+            subscriptions.push(TopicSubscription::new(topic));
+        }
+    }
+    subscriptions
+}
+
+/// Accept both the subscription list and the old comma-separated string
+pub fn deserialize_topics<'de, D>(deserializer: D) -> Result<Vec<TopicSubscription>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Topics {
+        List(Vec<TopicSubscription>),
+        Legacy(String),
+    }
+
+    Ok(match Topics::deserialize(deserializer)? {
+        Topics::List(subscriptions) => subscriptions,
+        Topics::Legacy(topics) => parse_topics(&topics),
+    })
+}
+
+/// Effective notification settings for one topic
+#[derive(Debug, Clone, PartialEq)]
+pub struct TopicSettings {
+    pub display_name: String,
+    pub muted: bool,
+    pub policies: PriorityPolicies,
+    pub icon: Option<String>,
+    pub hook: Option<HookCommand>,
+    pub journal: bool,
+    pub trusted: bool,
+}
+
+impl TopicSettings {
+    /// Policy for a priority; a rule marking the message urgent gets the urgent
+    /// policy, one marking it non-urgent the highest lower level that isn't urgent
+    pub fn policy(&self, priority: u8, urgent: Option<bool>) -> &PriorityPolicy {
+        let level = match urgent {
+            Some(true) => 5,
+            Some(false) => (1..=priority.clamp(1, 5))
+                .rev()
+                .find(|&level| !self.policies.get(level).urgent)
+                .unwrap_or(priority),
+            None => priority,
+        };
+        self.policies.get(level)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::AppConfig;
+
+    #[test]
+    fn test_legacy_topics_string_migrates() {
+        let config: AppConfig = serde_json::from_str(r#"{ "topics": "alerts, backups,,alerts" }"#).unwrap();
+        assert_eq!(config.topics, vec![TopicSubscription::new("alerts"), TopicSubscription::new("backups")]);
+
+        // Saved back as subscription objects
+        let json = serde_json::to_value(&config).unwrap();
+        assert_eq!(json["topics"][1]["topic"], "backups");
+        let reloaded: AppConfig = serde_json::from_value(json).unwrap();
+        assert_eq!(reloaded.topics, config.topics);
+    }
+
+    #[test]
+    fn test_muted_topics_migrate() {
+        let mut config: AppConfig =
+            serde_json::from_str(r#"{ "topics": "alerts,backups", "muted_topics": ["backups", "gone"] }"#).unwrap();
+        config.migrate();
+
+        assert!(!config.is_topic_muted("alerts"));
+        assert!(config.is_topic_muted("backups"));
+        assert_eq!(config.topics_list(), vec!["alerts", "backups"]);
+        assert!(config.muted_topics.is_empty());
+    }
+
+    #[test]
+    fn test_settings_fall_back_to_global_defaults() {
+        let mut config = AppConfig {
+            notification_sound: NotificationSound::Chime,
+            urgent_notification_sound: NotificationSound::Alert,
+            persistent_notifications_mode: PersistentNotificationMode::UrgentOnly,
+            // As in configs from before per-priority policies
+            priority_policies: None,
+            ..Default::default()
+        };
+        config.topics = vec![
+            TopicSubscription::new("plain"),
+            TopicSubscription {
+                topic: "ci".to_string(),
+                display_name: Some("CI builds".to_string()),
+                sound: Some(NotificationSound::None),
+                persistence: Some(PersistentNotificationMode::All),
+                urgent_priority_threshold: Some(5),
+                icon: Some("/icons/ci.png".to_string()),
+                journal: true,
+                trusted: true,
+                ..Default::default()
+            },
+        ];
+
+        let plain = config.topic_settings("plain");
+        assert_eq!(plain.display_name, "plain");
+        assert_eq!(plain.policy(3, None).sound, NotificationSound::Chime);
+        assert!(plain.policy(4, None).persistent && !plain.policy(3, None).persistent);
+        assert!(plain.policy(4, None).urgent && !plain.policy(3, None).urgent);
+        // A rule marking a message urgent gets the urgent policy, non-urgent the highest one below
+        assert_eq!(plain.policy(1, Some(true)), plain.policies.get(5));
+        assert_eq!(plain.policy(5, Some(false)), plain.policies.get(3));
+        assert_eq!(plain.policy(2, Some(false)), plain.policies.get(2));
+
+        // Overrides from before per-priority policies apply until migrated
+        let ci = config.topic_settings("ci");
+        assert_eq!(ci.display_name, "CI builds");
+        assert_eq!(ci.policy(4, None).sound, NotificationSound::None);
+        assert_eq!(ci.policy(5, None).sound, NotificationSound::Alert);
+        assert!(!ci.policy(4, None).urgent && ci.policy(5, None).urgent);
+        assert!(ci.policy(1, None).persistent);
+        assert_eq!(ci.icon.as_deref(), Some("/icons/ci.png"));
+        assert!(ci.journal && !plain.journal);
+        assert!(ci.trusted && !plain.trusted);
+
+        // Unsubscribed topics (e.g. UnifiedPush) use the global values
+        assert_eq!(config.topic_settings("other"), plain_named(&plain, "other"));
+    }
+
+    #[test]
+    fn test_threshold_migrates_to_priority_policies() {
+        let mut config: AppConfig = serde_json::from_str(
+            r#"{ "urgent_priority_threshold": 5, "notification_sound": "pop", "persistent_notifications_mode": "all" }"#,
+        )
+        .unwrap();
+        assert_eq!(config.priority_policies, None);
+        config.migrate();
+
+        let policies = config.priority_policies.clone().unwrap();
+        assert_eq!(policies.get(4).sound, NotificationSound::Pop);
+        assert_eq!(policies.get(5).sound, NotificationSound::Default);
+        assert!(policies.get(1).popup && policies.get(1).persistent);
+
+        // Saved policies are kept as they are
+        let json = serde_json::to_string(&config).unwrap();
+        let reloaded: AppConfig = serde_json::from_str(&json).unwrap();
+        assert_eq!(reloaded.priority_policies, Some(policies));
+        assert_eq!(AppConfig::default().priority_policies, Some(PriorityPolicies::default()));
+    }
+
+    #[test]
+    fn test_topic_policies_replace_global_ones() {
+        let quiet = PriorityPolicies {
+            high: PriorityPolicy { popup: false, sound: NotificationSound::None, ..Default::default() },
+            ..Default::default()
+        };
+        let config = AppConfig {
+            topics: vec![TopicSubscription { priority_policies: Some(quiet.clone()), ..TopicSubscription::new("backups") }],
+            ..Default::default()
+        };
+
+        let backups = config.topic_settings("backups");
+        assert_eq!(backups.policies, quiet);
+        assert!(!backups.policy(4, None).popup);
+        assert!(config.topic_settings("other").policy(4, None).popup);
+    }
+
+    #[test]
+    fn test_topic_overrides_migrate_to_priority_policies() {
+        let mut config: AppConfig = serde_json::from_str(
+            r#"{
+                "notification_sound": "chime",
+                "topics": [
+                    { "topic": "ci", "sound": "none", "persistence": "all", "urgent_priority_threshold": 5 },
+                    { "topic": "plain" }
+                ]
+            }"#,
+        )
+        .unwrap();
+        let before = config.topic_settings("ci");
+        config.migrate();
+
+        let ci = config.subscription("ci").unwrap();
+        assert!(!ci.has_legacy_overrides());
+        assert_eq!(ci.priority_policies.as_ref(), Some(&before.policies));
+        assert_eq!(config.topic_settings("ci"), before);
+        assert_eq!(before.policy(4, None).sound, NotificationSound::None);
+        assert_eq!(before.policy(3, None).sound, NotificationSound::None);
+        assert_eq!(before.policy(5, None).sound, NotificationSound::Default);
+        assert!(before.policy(2, None).persistent);
+
+        // Topics without overrides keep following the global policies
+        assert_eq!(config.subscription("plain").unwrap().priority_policies, None);
+
+        // The old fields are not written back
+        let json = serde_json::to_value(&config).unwrap();
+        assert!(json["topics"][0].get("urgent_priority_threshold").is_none());
+        assert!(json["topics"][0].get("sound").is_none());
+    }
+
+    fn plain_named(settings: &TopicSettings, name: &str) -> TopicSettings {
+        TopicSettings { display_name: name.to_string(), ..settings.clone() }
+    }
+
+    #[test]
+    fn test_mute_expiry() {
+        let mut sub = TopicSubscription::new("alerts");
+        assert!(!sub.is_muted_at(100));
+
+        sub.muted = true;
+        assert!(sub.is_muted_at(100));
+
+        sub.muted_until = Some(200);
+        assert!(sub.is_muted_at(199));
+        assert!(!sub.is_muted_at(200));
+    }
+}
@@ -0,0 +1,43 @@
+//! Message tags as shown by the ntfy web and Android apps: tags that are emoji
+//! shortcodes (`warning`, `rotating_light`, `+1`) prefix the title, the rest
+//! are listed in the body. See https://docs.ntfy.sh/publish/#tags-emojis
+
+/// Emojis for the shortcode tags and the remaining tags, both in message order
+pub fn split(tags: &[String]) -> (Vec<&'static str>, Vec<String>) {
+    let mut emojis = Vec::new();
+    let mut others = Vec::new();
+    for tag in tags.iter().map(|t| t.trim()).filter(|t| !t.is_empty()) {
+        match emojis::get_by_shortcode(tag) {
+            Some(emoji) => emojis.push(emoji.as_str()),
+            None => others.push(tag.to_string()),
+        }
+    }
+    (emojis, others)
+}
+
+/// `text` with the emojis in front, separated by spaces
+pub fn prefix(emojis: &[&str], text: &str) -> String {
+    if emojis.is_empty() {
+        return text.to_string();
+    }
+    format!("{} {}", emojis.join(" "), text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tags(list: &[&str]) -> Vec<String> {
+        list.iter().map(|t| t.to_string()).collect()
+    }
+
+    #[test]
+    fn test_split_tags() {
+        let (emojis, others) = split(&tags(&["warning", "backup", "rotating_light", "+1", "", "db-01"]));
+        assert_eq!(emojis, vec!["⚠️", "🚨", "👍"]);
+        assert_eq!(others, vec!["backup", "db-01"]);
+
+        assert_eq!(prefix(&emojis, "Backup failed"), "⚠️ 🚨 👍 Backup failed");
+        assert_eq!(prefix(&[], "Backup failed"), "Backup failed");
+    }
+}
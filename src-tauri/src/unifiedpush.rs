@@ -0,0 +1,525 @@
+//! UnifiedPush distributor over D-Bus (Linux).
+//!
+//! Apps register through `org.unifiedpush.Distributor1`, get a unique ntfy topic each,
+//! and receive messages on that topic through their `org.unifiedpush.Connector1` endpoint.
+//! See https://unifiedpush.org/developers/spec/dbus/
+
+use anyhow::Result;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use rand::distributions::Alphanumeric;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use zbus::{interface, Connection};
+
+use super::config::AppConfig;
+use super::ntfy::NtfyMessage;
+
+/// Well-known bus name claimed by this distributor
+pub const DISTRIBUTOR_BUS_NAME: &str = "org.unifiedpush.Distributor.ntfy_desktop";
+pub const DISTRIBUTOR_PATH: &str = "/org/unifiedpush/Distributor";
+pub const CONNECTOR_PATH: &str = "/org/unifiedpush/Connector";
+pub const CONNECTOR_INTERFACE: &str = "org.unifiedpush.Connector1";
+
+const REGISTRATION_SUCCEEDED: &str = "REGISTRATION_SUCCEEDED";
+const REGISTRATION_FAILED: &str = "REGISTRATION_FAILED";
+
+/// A registered app: one connector token mapped to one ntfy topic
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Registration {
+    pub token: String,
+    pub service_name: String,
+    #[serde(default)]
+    pub description: String,
+    pub topic: String,
+}
+
+/// Registrations persisted as JSON in the app data directory
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Registry {
+    registrations: Vec<Registration>,
+    #[serde(skip)]
+    path: Option<PathBuf>,
+    /// Bumped on every change so stale snapshots are never written over newer ones
+    #[serde(skip)]
+    version: u64,
+}
+
+/// Registry contents serialized under the registry lock, written once it is released
+pub struct RegistrySnapshot {
+    path: PathBuf,
+    data: String,
+    version: u64,
+}
+
+impl RegistrySnapshot {
+    /// Write the snapshot unless a newer one already was. `written` holds the last
+    /// written version and keeps concurrent writers in order.
+    pub async fn write(self, written: &Mutex<u64>) -> Result<()> {
+        let mut written = written.lock().await;
+        if self.version <= *written {
+            return Ok(());
+        }
+        if let Some(dir) = self.path.parent() {
+            tokio::fs::create_dir_all(dir).await?;
+        }
+        tokio::fs::write(&self.path, self.data)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to write UnifiedPush registry {}: {}", self.path.display(), e))?;
+        *written = self.version;
+        Ok(())
+    }
+}
+
+impl Registry {
+    /// Load the registry from disk, starting empty if the file is missing or unreadable
+    pub fn load(path: &Path) -> Self {
+        let mut registry = match std::fs::read_to_string(path) {
+            Ok(data) => serde_json::from_str::<Registry>(&data).unwrap_or_else(|e| {
+                eprintln!("Failed to parse UnifiedPush registry {}: {}", path.display(), e);
+                Registry::default()
+            }),
+            Err(_) => Registry::default(),
+        };
+        registry.path = Some(path.to_path_buf());
+        registry
+    }
+
+    /// Current contents for writing to disk; None for a registry without a file
+    pub fn snapshot(&self) -> Result<Option<RegistrySnapshot>> {
+        let Some(path) = &self.path else {
+            return Ok(None);
+        };
+        Ok(Some(RegistrySnapshot {
+            path: path.clone(),
+            data: serde_json::to_string_pretty(self)?,
+            version: self.version,
+        }))
+    }
+
+    /// Register an app. Re-registering a known token keeps its topic (and thus its endpoint).
+    pub fn register(&mut self, service_name: &str, token: &str, description: &str) -> Result<Registration> {
+        if service_name.is_empty() || token.is_empty() {
+            return Err(anyhow::anyhow!("Service name and token are required"));
+        }
+
+        if let Some(existing) = self.registrations.iter_mut().find(|r| r.token == token) {
+            existing.service_name = service_name.to_string();
+            existing.description = description.to_string();
+        } else {
+            self.registrations.push(Registration {
+                token: token.to_string(),
+                service_name: service_name.to_string(),
+                description: description.to_string(),
+                topic: generate_topic(),
+            });
+        }
+
+        self.version += 1;
+        Ok(self.registrations.iter().find(|r| r.token == token).cloned().expect("registration just stored"))
+    }
+
+    /// Remove a registration by token
+    pub fn unregister(&mut self, token: &str) -> Option<Registration> {
+        let index = self.registrations.iter().position(|r| r.token == token)?;
+        self.version += 1;
+        Some(self.registrations.remove(index))
+    }
+
+    pub fn registrations(&self) -> &[Registration] {
+        &self.registrations
+    }
+
+    /// Topics to subscribe to on behalf of registered apps
+    pub fn topics(&self) -> Vec<String> {
+        self.registrations.iter().map(|r| r.topic.clone()).collect()
+    }
+
+    pub fn by_topic(&self, topic: &str) -> Option<&Registration> {
+        self.registrations.iter().find(|r| r.topic == topic)
+    }
+}
+
+/// Unique, unguessable topic for a registration (same shape as the ntfy Android app uses)
+pub fn generate_topic() -> String {
+    let suffix: String = rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(12)
+        .map(char::from)
+        .collect();
+    format!("up{}", suffix)
+}
+
+/// Push endpoint handed to the app: application servers POST to it
+pub fn endpoint_url(base_url: &str, topic: &str) -> String {
+    format!("{}/{}?up=1", base_url.trim_end_matches('/'), topic)
+}
+
+/// Raw push payload of a message. Binary payloads arrive base64-encoded from ntfy.
+pub fn message_payload(msg: &NtfyMessage) -> Vec<u8> {
+    let body = msg.message.clone().unwrap_or_default();
+    if msg.encoding.as_deref() == Some("base64") {
+        match BASE64.decode(body.trim()) {
+            Ok(bytes) => return bytes,
+            Err(e) => eprintln!("Failed to decode base64 UnifiedPush message {:?}: {}", msg.id, e),
+        }
+    }
+    body.into_bytes()
+}
+
+/// Tell a connector about its (new) endpoint
+async fn send_new_endpoint(conn: &Connection, registration: &Registration, endpoint: &str) -> Result<()> {
+    conn.call_method(
+        Some(registration.service_name.as_str()),
+        CONNECTOR_PATH,
+        Some(CONNECTOR_INTERFACE),
+        "NewEndpoint",
+        &(registration.token.as_str(), endpoint),
+    )
+    .await?;
+    Ok(())
+}
+
+/// Tell a connector it is no longer registered
+async fn send_unregistered(conn: &Connection, registration: &Registration) -> Result<()> {
+    conn.call_method(
+        Some(registration.service_name.as_str()),
+        CONNECTOR_PATH,
+        Some(CONNECTOR_INTERFACE),
+        "Unregistered",
+        &(registration.token.as_str(),),
+    )
+    .await?;
+    Ok(())
+}
+
+/// D-Bus object implementing `org.unifiedpush.Distributor1`
+struct Distributor {
+    registry: Arc<Mutex<Registry>>,
+    config: Arc<Mutex<AppConfig>>,
+    /// Last registry version written to disk
+    written: Mutex<u64>,
+}
+
+impl Distributor {
+    /// Write the registry without holding its lock during file I/O
+    async fn persist(&self, snapshot: Option<RegistrySnapshot>) -> Result<()> {
+        match snapshot {
+            Some(snapshot) => snapshot.write(&self.written).await,
+            None => Ok(()),
+        }
+    }
+}
+
+#[interface(name = "org.unifiedpush.Distributor1")]
+impl Distributor {
+    async fn register(
+        &self,
+        service_name: String,
+        token: String,
+        description: String,
+        #[zbus(connection)] conn: &Connection,
+    ) -> (String, String) {
+        let registered = {
+            let mut registry = self.registry.lock().await;
+            registry
+                .register(&service_name, &token, &description)
+                .and_then(|registration| Ok((registration, registry.snapshot()?)))
+        };
+        let registered = match registered {
+            Ok((registration, snapshot)) => self.persist(snapshot).await.map(|_| registration),
+            Err(e) => Err(e),
+        };
+        let registration = match registered {
+            Ok(registration) => registration,
+            Err(e) => {
+                eprintln!("UnifiedPush registration for {} failed: {}", service_name, e);
+                return (REGISTRATION_FAILED.to_string(), e.to_string());
+            }
+        };
+        println!("UnifiedPush: registered {} on topic {}", service_name, registration.topic);
+
+        // The endpoint is delivered via the connector once this call has returned
+        let endpoint = endpoint_url(&self.config.lock().await.api_base_url(), &registration.topic);
+        let conn = conn.clone();
+        tokio::spawn(async move {
+            if let Err(e) = send_new_endpoint(&conn, &registration, &endpoint).await {
+                eprintln!("UnifiedPush: failed to send endpoint to {}: {}", registration.service_name, e);
+            }
+        });
+
+        (REGISTRATION_SUCCEEDED.to_string(), String::new())
+    }
+
+    async fn unregister(&self, token: String, #[zbus(connection)] conn: &Connection) {
+        let (removed, snapshot) = {
+            let mut registry = self.registry.lock().await;
+            let removed = registry.unregister(&token);
+            (removed, registry.snapshot())
+        };
+        if removed.is_some() {
+            let persisted = match snapshot {
+                Ok(snapshot) => self.persist(snapshot).await,
+                Err(e) => Err(e),
+            };
+            if let Err(e) = persisted {
+                eprintln!("UnifiedPush: failed to save registry after unregistering: {}", e);
+            }
+        }
+
+        if let Some(registration) = removed {
+            println!("UnifiedPush: unregistered {}", registration.service_name);
+            let conn = conn.clone();
+            tokio::spawn(async move {
+                if let Err(e) = send_unregistered(&conn, &registration).await {
+                    eprintln!("UnifiedPush: failed to notify {} of unregistration: {}", registration.service_name, e);
+                }
+            });
+        }
+    }
+}
+
+/// Running distributor, registered as managed state so the poller can route push topics
+pub struct UnifiedPushState {
+    registry: Arc<Mutex<Registry>>,
+    connection: Connection,
+}
+
+impl UnifiedPushState {
+    /// Topics subscribed on behalf of registered apps
+    pub async fn topics(&self) -> Vec<String> {
+        self.registry.lock().await.topics()
+    }
+
+    /// Forward a message to the app registered for its topic.
+    /// Returns false if the topic does not belong to any registration.
+    pub async fn deliver(&self, msg: &NtfyMessage) -> bool {
+        let Some(topic) = msg.topic.as_deref() else {
+            return false;
+        };
+        let Some(registration) = self.registry.lock().await.by_topic(topic).cloned() else {
+            return false;
+        };
+
+        let id = msg.id.clone().unwrap_or_default();
+        let result = self
+            .connection
+            .call_method(
+                Some(registration.service_name.as_str()),
+                CONNECTOR_PATH,
+                Some(CONNECTOR_INTERFACE),
+                "Message",
+                &(registration.token.as_str(), message_payload(msg), id.as_str()),
+            )
+            .await;
+
+        if let Err(e) = result {
+            eprintln!("UnifiedPush: failed to deliver message to {}: {}", registration.service_name, e);
+        }
+        true
+    }
+}
+
+/// Claim the distributor bus name on the session bus and start serving registrations
+pub async fn start(registry_path: &Path, config: Arc<Mutex<AppConfig>>) -> Result<UnifiedPushState> {
+    let registry = Arc::new(Mutex::new(Registry::load(registry_path)));
+    let connection = serve(zbus::connection::Builder::session()?, registry.clone(), config).await?;
+
+    println!(
+        "UnifiedPush distributor running as {} ({} registrations)",
+        DISTRIBUTOR_BUS_NAME,
+        registry.lock().await.registrations().len()
+    );
+
+    Ok(UnifiedPushState { registry, connection })
+}
+
+async fn serve(
+    builder: zbus::connection::Builder<'_>,
+    registry: Arc<Mutex<Registry>>,
+    config: Arc<Mutex<AppConfig>>,
+) -> Result<Connection> {
+    let connection = builder
+        .name(DISTRIBUTOR_BUS_NAME)?
+        .serve_at(DISTRIBUTOR_PATH, Distributor { registry, config, written: Mutex::new(0) })?
+        .build()
+        .await?;
+    Ok(connection)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    async fn save(registry: &Registry, written: &Mutex<u64>) {
+        registry.snapshot().unwrap().unwrap().write(written).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_register_and_unregister() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("unifiedpush.json");
+        let written = Mutex::new(0);
+        let mut registry = Registry::load(&path);
+
+        let first = registry.register("org.example.Chat", "token-1", "Chat").unwrap();
+        assert!(first.topic.starts_with("up"));
+        assert_eq!(first.topic.len(), 14);
+
+        // Re-registering keeps the topic so the app server's endpoint stays valid
+        let again = registry.register("org.example.Chat", "token-1", "Chat v2").unwrap();
+        assert_eq!(again.topic, first.topic);
+        assert_eq!(again.description, "Chat v2");
+
+        let second = registry.register("org.example.Social", "token-2", "").unwrap();
+        assert_ne!(second.topic, first.topic);
+        assert_eq!(registry.topics().len(), 2);
+
+        // Persisted across reloads
+        save(&registry, &written).await;
+        let reloaded = Registry::load(&path);
+        assert_eq!(reloaded.registrations().len(), 2);
+        assert_eq!(reloaded.by_topic(&first.topic).unwrap().token, "token-1");
+
+        // A snapshot taken before the latest change never overwrites it
+        let stale = registry.snapshot().unwrap().unwrap();
+        let removed = registry.unregister("token-1").unwrap();
+        assert_eq!(removed.service_name, "org.example.Chat");
+        assert!(registry.unregister("token-1").is_none());
+        save(&registry, &written).await;
+        stale.write(&written).await.unwrap();
+        assert_eq!(Registry::load(&path).topics(), vec![second.topic]);
+        assert!(Registry::default().snapshot().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_register_requires_service_and_token() {
+        let mut registry = Registry::default();
+
+        assert!(registry.register("", "token", "").is_err());
+        assert!(registry.register("org.example.Chat", "", "").is_err());
+        assert!(registry.registrations().is_empty());
+    }
+
+    #[test]
+    fn test_endpoint_and_payload() {
+        assert_eq!(endpoint_url("https://ntfy.sh/", "upAbc"), "https://ntfy.sh/upAbc?up=1");
+
+        let mut msg: NtfyMessage =
+            serde_json::from_value(serde_json::json!({ "id": "m1", "message": "aGVsbG8=", "encoding": "base64" }))
+                .unwrap();
+        assert_eq!(message_payload(&msg), b"hello");
+
+        msg.encoding = None;
+        assert_eq!(message_payload(&msg), b"aGVsbG8=");
+    }
+
+    /// Connector stand-in that records every call it receives
+    #[derive(Default, Clone)]
+    struct FakeConnector {
+        calls: Arc<std::sync::Mutex<Vec<String>>>,
+    }
+
+    #[interface(name = "org.unifiedpush.Connector1")]
+    impl FakeConnector {
+        fn new_endpoint(&self, token: String, endpoint: String) {
+            self.calls.lock().unwrap().push(format!("endpoint {} {}", token, endpoint));
+        }
+
+        fn message(&self, token: String, message: Vec<u8>, id: String) {
+            let text = String::from_utf8_lossy(&message).to_string();
+            self.calls.lock().unwrap().push(format!("message {} {} {}", token, id, text));
+        }
+
+        fn unregistered(&self, token: String) {
+            self.calls.lock().unwrap().push(format!("unregistered {}", token));
+        }
+    }
+
+    async fn wait_for_calls(connector: &FakeConnector, count: usize) -> Vec<String> {
+        for _ in 0..50 {
+            if connector.calls.lock().unwrap().len() >= count {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        }
+        connector.calls.lock().unwrap().clone()
+    }
+
+    /// Full register/deliver/unregister round trip over a real session bus.
+    /// Skipped when no session bus is available (e.g. headless CI without dbus-run-session).
+    #[tokio::test]
+    async fn test_distributor_over_session_bus() {
+        if std::env::var_os("DBUS_SESSION_BUS_ADDRESS").is_none() {
+            eprintln!("Skipping: no D-Bus session bus");
+            return;
+        }
+
+        let temp_dir = TempDir::new().unwrap();
+        let registry = Arc::new(Mutex::new(Registry::load(&temp_dir.path().join("unifiedpush.json"))));
+        let config = Arc::new(Mutex::new(AppConfig {
+            instance_url: "https://ntfy.example.com/app".to_string(),
+            ..Default::default()
+        }));
+        let distributor_conn = serve(zbus::connection::Builder::session().unwrap(), registry.clone(), config)
+            .await
+            .unwrap();
+        let state = UnifiedPushState { registry, connection: distributor_conn };
+
+        let connector = FakeConnector::default();
+        let app_conn = zbus::connection::Builder::session()
+            .unwrap()
+            .serve_at(CONNECTOR_PATH, connector.clone())
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+        let app_name = app_conn.unique_name().unwrap().to_string();
+
+        let reply = app_conn
+            .call_method(
+                Some(DISTRIBUTOR_BUS_NAME),
+                DISTRIBUTOR_PATH,
+                Some("org.unifiedpush.Distributor1"),
+                "Register",
+                &(app_name.as_str(), "token-1", "Test app"),
+            )
+            .await
+            .unwrap();
+        let (result, reason): (String, String) = reply.body().deserialize().unwrap();
+        assert_eq!(result, REGISTRATION_SUCCEEDED);
+        assert!(reason.is_empty());
+
+        let calls = wait_for_calls(&connector, 1).await;
+        let topic = state.topics().await.pop().unwrap();
+        assert_eq!(calls[0], format!("endpoint token-1 https://ntfy.example.com/{}?up=1", topic));
+
+        let msg: NtfyMessage =
+            serde_json::from_value(serde_json::json!({ "id": "m1", "topic": topic, "message": "ping" })).unwrap();
+        assert!(state.deliver(&msg).await);
+        let calls = wait_for_calls(&connector, 2).await;
+        assert_eq!(calls[1], "message token-1 m1 ping");
+
+        let other: NtfyMessage =
+            serde_json::from_value(serde_json::json!({ "id": "m2", "topic": "alerts", "message": "x" })).unwrap();
+        assert!(!state.deliver(&other).await);
+
+        app_conn
+            .call_method(
+                Some(DISTRIBUTOR_BUS_NAME),
+                DISTRIBUTOR_PATH,
+                Some("org.unifiedpush.Distributor1"),
+                "Unregister",
+                &("token-1",),
+            )
+            .await
+            .unwrap();
+        let calls = wait_for_calls(&connector, 3).await;
+        assert_eq!(calls[2], "unregistered token-1");
+        assert!(state.topics().await.is_empty());
+    }
+}
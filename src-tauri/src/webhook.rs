@@ -0,0 +1,311 @@
+//! Webhook sinks: incoming messages are POSTed to configured URLs, as the
+//! message JSON or a user-defined template. Header values (e.g. tokens) live
+//! in the OS keychain, see `credentials::load_webhook_headers`.
+
+use anyhow::Result;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+use super::ntfy::NtfyMessage;
+
+/// Delay before the first retry; doubled for each further attempt
+const RETRY_BASE_DELAY: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct WebhookSink {
+    /// Identifies the sink (and its headers in the keychain)
+    pub name: String,
+    pub enabled: bool,
+    pub url: String,
+    /// Only forward these topics (empty = all topics)
+    pub topics: Vec<String>,
+    /// Request body; `{topic}`, `{title}`, `{message}`, `{priority}`, `{tags}`,
+    /// `{id}`, `{time}` and `{click}` are replaced. Empty sends the message JSON.
+    pub template: String,
+    pub content_type: String,
+    /// Retries after the first attempt for network errors, 429 and 5xx responses
+    pub max_retries: u32,
+}
+
+impl Default for WebhookSink {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            enabled: true,
+            url: String::new(),
+            topics: Vec::new(),
+            template: String::new(),
+            content_type: "application/json".to_string(),
+            max_retries: 3,
+        }
+    }
+}
+
+impl WebhookSink {
+    pub fn accepts(&self, topic: &str) -> bool {
+        self.enabled && !self.url.is_empty() && (self.topics.is_empty() || self.topics.iter().any(|t| t == topic))
+    }
+
+    /// Request body for a message
+    pub fn body(&self, msg: &NtfyMessage) -> Result<String> {
+        if self.template.trim().is_empty() {
+            return Ok(serde_json::to_string(msg)?);
+        }
+        Ok(render_template(&self.template, msg, self.content_type.contains("json")))
+    }
+}
+
+/// Escape a value for use inside a JSON string literal
+fn json_escape(value: &str) -> String {
+    let quoted = serde_json::to_string(value).unwrap_or_default();
+    quoted[1..quoted.len() - 1].to_string()
+}
+
+/// Fill in message fields; values are JSON-escaped for JSON templates
+pub fn render_template(template: &str, msg: &NtfyMessage, escape_json: bool) -> String {
+    let fields = [
+        ("{topic}", msg.topic.clone().unwrap_or_default()),
+        ("{title}", msg.title.clone().unwrap_or_default()),
+        ("{message}", msg.message.clone().unwrap_or_default()),
+        ("{priority}", msg.priority.unwrap_or(3).to_string()),
+        ("{tags}", msg.tags.clone().unwrap_or_default().join(",")),
+        ("{id}", msg.id.clone().unwrap_or_default()),
+        ("{time}", msg.time.to_string()),
+        ("{click}", msg.click.clone().unwrap_or_default()),
+    ];
+    let mut body = template.to_string();
+    for (placeholder, value) in fields {
+        let value = if escape_json { json_escape(&value) } else { value };
+        body = body.replace(placeholder, &value);
+    }
+    body
+}
+
+/// Sends messages to webhook sinks
+#[derive(Clone)]
+pub struct WebhookForwarder {
+    client: Client,
+    retry_delay: Duration,
+}
+
+impl Default for WebhookForwarder {
+    fn default() -> Self {
+        Self::new(RETRY_BASE_DELAY)
+    }
+}
+
+impl WebhookForwarder {
+    pub fn new(retry_delay: Duration) -> Self {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(30))
+            .user_agent(format!("ntfy-desktop/{}", env!("CARGO_PKG_VERSION")))
+            .build()
+            .unwrap_or_default();
+        Self { client, retry_delay }
+    }
+
+    /// POST the message, retrying with exponential backoff. Returns the number of attempts made.
+    pub async fn deliver(&self, sink: &WebhookSink, headers: &[(String, String)], msg: &NtfyMessage) -> Result<u32> {
+        let body = sink.body(msg)?;
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+            let mut request = self
+                .client
+                .post(&sink.url)
+                .header(reqwest::header::CONTENT_TYPE, &sink.content_type)
+                .body(body.clone());
+            for (name, value) in headers {
+                request = request.header(name.as_str(), value.as_str());
+            }
+
+            let error = match request.send().await {
+                Ok(response) if response.status().is_success() => return Ok(attempt),
+                Ok(response) => {
+                    let status = response.status();
+                    let error = anyhow::anyhow!("Webhook '{}' returned {}", sink.name, status);
+                    if !(status.is_server_error() || status == reqwest::StatusCode::TOO_MANY_REQUESTS) {
+                        return Err(error);
+                    }
+                    error
+                }
+                Err(e) => anyhow::anyhow!("Webhook '{}' request failed: {}", sink.name, e),
+            };
+
+            if attempt > sink.max_retries {
+                return Err(error);
+            }
+            let delay = self.retry_delay * 2u32.saturating_pow(attempt - 1);
+            eprintln!("{}; retrying in {:?}", error, delay);
+            tokio::time::sleep(delay).await;
+        }
+    }
+
+    /// Forward a message to every sink that accepts its topic, in the background
+    pub fn forward(&self, sinks: &[WebhookSink], msg: &NtfyMessage) {
+        let topic = msg.topic.clone().unwrap_or_default();
+        for sink in sinks.iter().filter(|s| s.accepts(&topic)) {
+            let forwarder = self.clone();
+            let sink = sink.clone();
+            let msg = msg.clone();
+            tauri::async_runtime::spawn(async move {
+                let headers = super::credentials::load_webhook_headers(&sink.name).unwrap_or_else(|e| {
+                    eprintln!("Failed to load headers for webhook '{}': {}", sink.name, e);
+                    Vec::new()
+                });
+                if let Err(e) = forwarder.deliver(&sink, &headers, &msg).await {
+                    eprintln!("Failed to forward message to webhook '{}': {}", sink.name, e);
+                }
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    #[derive(Debug, Clone)]
+    struct Received {
+        head: String,
+        body: String,
+    }
+
+    /// Minimal HTTP server answering with the given statuses in turn (then 200)
+    async fn stand_in(statuses: Vec<u16>) -> (String, Arc<Mutex<Vec<Received>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let log = received.clone();
+
+        tokio::spawn(async move {
+            let mut statuses = statuses.into_iter();
+            loop {
+                let Ok((mut stream, _)) = listener.accept().await else { return };
+                let mut data = Vec::new();
+                let mut buf = [0u8; 4096];
+                // Read headers, then as much body as Content-Length says
+                let (head, body) = loop {
+                    let n = stream.read(&mut buf).await.unwrap();
+                    data.extend_from_slice(&buf[..n]);
+                    let text = String::from_utf8_lossy(&data).to_string();
+                    if let Some(split) = text.find("\r\n\r\n") {
+                        let head = text[..split].to_string();
+                        let length = head
+                            .lines()
+                            .find_map(|l| l.to_ascii_lowercase().strip_prefix("content-length:").map(|v| v.trim().to_string()))
+                            .and_then(|v| v.parse::<usize>().ok())
+                            .unwrap_or(0);
+                        if data.len() >= split + 4 + length {
+                            break (head, text[split + 4..].to_string());
+                        }
+                    }
+                    if n == 0 {
+                        break (text, String::new());
+                    }
+                };
+                log.lock().unwrap().push(Received { head, body });
+
+                let status = statuses.next().unwrap_or(200);
+                let response = format!("HTTP/1.1 {} Status\r\nContent-Length: 0\r\nConnection: close\r\n\r\n", status);
+                let _ = stream.write_all(response.as_bytes()).await;
+            }
+        });
+        (url, received)
+    }
+
+    fn message() -> NtfyMessage {
+        serde_json::from_value(serde_json::json!({
+            "id": "m1",
+            "time": 1700000000,
+            "topic": "alerts",
+            "title": "Disk \"full\"",
+            "message": "95% used",
+            "priority": 5,
+            "tags": ["warning"],
+        }))
+        .unwrap()
+    }
+
+    fn sink(url: &str) -> WebhookSink {
+        WebhookSink { name: "dashboard".to_string(), url: url.to_string(), ..Default::default() }
+    }
+
+    #[tokio::test]
+    async fn test_posts_message_json_with_headers() {
+        let (url, received) = stand_in(vec![]).await;
+        let forwarder = WebhookForwarder::new(Duration::from_millis(10));
+        let headers = vec![("Authorization".to_string(), "Bearer secret".to_string())];
+
+        let attempts = forwarder.deliver(&sink(&url), &headers, &message()).await.unwrap();
+        assert_eq!(attempts, 1);
+
+        let received = received.lock().unwrap();
+        assert!(received[0].head.starts_with("POST /hook"));
+        assert!(received[0].head.to_ascii_lowercase().contains("authorization: bearer secret"));
+        let body: serde_json::Value = serde_json::from_str(&received[0].body).unwrap();
+        assert_eq!(body["topic"], "alerts");
+        assert_eq!(body["priority"], 5);
+    }
+
+    #[tokio::test]
+    async fn test_template_body() {
+        let (url, received) = stand_in(vec![]).await;
+        let forwarder = WebhookForwarder::new(Duration::from_millis(10));
+        let sink = WebhookSink {
+            template: r#"{"text": "[{topic}] {title}: {message}", "level": {priority}}"#.to_string(),
+            ..sink(&url)
+        };
+
+        forwarder.deliver(&sink, &[], &message()).await.unwrap();
+        let body: serde_json::Value = serde_json::from_str(&received.lock().unwrap()[0].body).unwrap();
+        assert_eq!(body["text"], "[alerts] Disk \"full\": 95% used");
+        assert_eq!(body["level"], 5);
+
+        let plain = render_template("{title} ({tags})", &message(), false);
+        assert_eq!(plain, "Disk \"full\" (warning)");
+    }
+
+    #[tokio::test]
+    async fn test_retries_server_errors_with_backoff() {
+        let (url, received) = stand_in(vec![503, 500]).await;
+        let forwarder = WebhookForwarder::new(Duration::from_millis(50));
+
+        let started = std::time::Instant::now();
+        let attempts = forwarder.deliver(&sink(&url), &[], &message()).await.unwrap();
+        assert_eq!(attempts, 3);
+        assert_eq!(received.lock().unwrap().len(), 3);
+        // 50ms + 100ms of backoff
+        assert!(started.elapsed() >= Duration::from_millis(150));
+    }
+
+    #[tokio::test]
+    async fn test_gives_up() {
+        let (url, received) = stand_in(vec![500, 500, 500]).await;
+        let forwarder = WebhookForwarder::new(Duration::from_millis(1));
+        let retry_once = WebhookSink { max_retries: 1, ..sink(&url) };
+        assert!(forwarder.deliver(&retry_once, &[], &message()).await.is_err());
+        assert_eq!(received.lock().unwrap().len(), 2);
+
+        // Client errors are not retried
+        let (url, received) = stand_in(vec![404]).await;
+        assert!(forwarder.deliver(&sink(&url), &[], &message()).await.is_err());
+        assert_eq!(received.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_topic_filter() {
+        let mut sink = sink("http://localhost/hook");
+        assert!(sink.accepts("anything"));
+        sink.topics = vec!["alerts".to_string()];
+        assert!(sink.accepts("alerts"));
+        assert!(!sink.accepts("backups"));
+        sink.enabled = false;
+        assert!(!sink.accepts("alerts"));
+    }
+}
@@ -1,4 +1,5 @@
 use ntfy_desktop::config::AppConfig;
+use ntfy_desktop::subscriptions::parse_topics;
 use ntfy_desktop::notifications::NotificationManager;
 use tempfile::TempDir;
 use tokio::fs;
@@ -13,7 +14,7 @@ fn test_app_config_default_flow() {
     assert_eq!(config.api_base_url(), "https://ntfy.sh");
     
     // Verify default topics
-    assert_eq!(config.topics, "announcements,stats");
+    assert_eq!(config.topics_list(), vec!["announcements", "stats"]);
     assert_eq!(config.topics_list(), vec!["announcements", "stats"]);
     assert_eq!(config.topics_path(), "announcements,stats");
     
@@ -54,7 +55,7 @@ async fn test_config_save_load_flow() {
     // Create a comprehensive config
     let original_config = AppConfig {
         instance_url: "https://my-ntfy.example.com/app".to_string(),
-        topics: "alerts,updates,monitoring".to_string(),
+        topics: parse_topics("alerts,updates,monitoring"),
         poll_rate: 30,
         datetime_format: "MM/DD/YYYY HH:mm".to_string(),
         start_hidden: true,
@@ -137,20 +138,20 @@ async fn test_multiple_config_flow() {
     let configs = vec![
         ("config1", AppConfig {
             instance_url: "https://ntfy1.example.com/app".to_string(),
-            topics: "topic1".to_string(),
+            topics: parse_topics("topic1"),
             start_hidden: true,
             ..AppConfig::default()
         }),
         ("config2", AppConfig {
             instance_url: "https://ntfy2.example.com/app".to_string(),
-            topics: "topic2,topic3".to_string(),
+            topics: parse_topics("topic2,topic3"),
             start_hidden: false,
             quit_on_close: true,
             ..AppConfig::default()
         }),
         ("config3", AppConfig {
             instance_url: "https://ntfy3.example.com".to_string(), // No /app suffix
-            topics: "".to_string(), // Empty topics
+            topics: parse_topics(""), // Empty topics
             poll_rate: 5, // Minimum poll rate
             ..AppConfig::default()
         }),
@@ -226,7 +227,7 @@ fn test_config_methods_flow() {
     
     for (input_topics, expected_list, expected_path) in topics_cases {
         let config = AppConfig {
-            topics: input_topics.to_string(),
+            topics: parse_topics(input_topics),
             ..AppConfig::default()
         };
         
@@ -344,7 +345,7 @@ async fn test_error_recovery_flow() {
     if let Ok(partial_config) = partial_result {
         assert_eq!(partial_config.instance_url, "https://example.com");
         // Other fields should have default values
-        assert_eq!(partial_config.topics, "announcements,stats");
+        assert_eq!(partial_config.topics_list(), vec!["announcements", "stats"]);
         assert_eq!(partial_config.poll_rate, 60);
     }
 }
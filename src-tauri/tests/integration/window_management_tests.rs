@@ -128,7 +128,7 @@ async fn test_window_configuration_recovery() {
     
     // Verify explicit values
     assert_eq!(loaded_config.instance_url, "https://ntfy.sh/app");
-    assert_eq!(loaded_config.topics, "test-topic");
+    assert_eq!(loaded_config.topics_list(), vec!["test-topic"]);
     assert_eq!(loaded_config.poll_rate, 60);
     assert_eq!(loaded_config.datetime_format, "YYYY-MM-DD hh:mm a");
     assert!(loaded_config.start_hidden);
@@ -178,7 +178,7 @@ async fn test_window_configuration_migration() {
     
     // Verify values are loaded correctly
     assert_eq!(loaded_config.instance_url, "https://ntfy.sh/app");
-    assert_eq!(loaded_config.topics, "test-topic");
+    assert_eq!(loaded_config.topics_list(), vec!["test-topic"]);
     assert_eq!(loaded_config.poll_rate, 60);
     assert_eq!(loaded_config.datetime_format, "YYYY-MM-DD hh:mm a");
     assert!(loaded_config.persistent_notifications); // Deprecated but should load
@@ -1,4 +1,5 @@
 use ntfy_desktop::config::{AppConfig, PersistentNotificationMode, NotificationSound};
+use ntfy_desktop::subscriptions::parse_topics;
 use tempfile::TempDir;
 use tokio::fs;
 
@@ -10,7 +11,7 @@ fn test_config_default_values() {
     assert_eq!(config.api_token, "");
     assert_eq!(config.auth_user, "");
     assert_eq!(config.auth_pass, "");
-    assert_eq!(config.topics, "announcements,stats");
+    assert_eq!(config.topics_list(), vec!["announcements", "stats"]);
     assert_eq!(config.poll_rate, 60);
     assert_eq!(config.datetime_format, "YYYY-MM-DD hh:mm a");
     assert!(!config.persistent_notifications);
@@ -52,22 +53,22 @@ fn test_topics_parsing() {
     let mut config = AppConfig::default();
     
     // Normal comma-separated topics
-    config.topics = "topic1,topic2,topic3".to_string();
+    config.topics = parse_topics("topic1,topic2,topic3");
     assert_eq!(config.topics_list(), vec!["topic1", "topic2", "topic3"]);
     assert_eq!(config.topics_path(), "topic1,topic2,topic3");
     
     // Topics with spaces
-    config.topics = "topic1, topic2, topic3".to_string();
+    config.topics = parse_topics("topic1, topic2, topic3");
     assert_eq!(config.topics_list(), vec!["topic1", "topic2", "topic3"]);
     assert_eq!(config.topics_path(), "topic1,topic2,topic3");
     
     // Empty topics
-    config.topics = "".to_string();
+    config.topics = parse_topics("");
     assert_eq!(config.topics_list(), Vec::<String>::new());
     assert_eq!(config.topics_path(), "");
     
     // Topics with extra commas
-    config.topics = "topic1,,topic2,".to_string();
+    config.topics = parse_topics("topic1,,topic2,");
     assert_eq!(config.topics_list(), vec!["topic1", "topic2"]);
     assert_eq!(config.topics_path(), "topic1,topic2");
 }
@@ -135,7 +136,7 @@ async fn test_config_serialization_roundtrip() {
     
     let original_config = AppConfig {
         instance_url: "https://test.example.com".to_string(),
-        topics: "test-topic".to_string(),
+        topics: parse_topics("test-topic"),
         poll_rate: 120,
         datetime_format: "YYYY-MM-DD HH:mm".to_string(),
         persistent_notifications: true,
@@ -198,7 +199,7 @@ async fn test_config_with_special_characters() {
     
     let config = AppConfig {
         instance_url: "https://example.com/path?query=test&param=value".to_string(),
-        topics: "topic-with-ünicode,another-topic".to_string(),
+        topics: parse_topics("topic-with-ünicode,another-topic"),
         poll_rate: 123,
         datetime_format: "YYYY年MM月DD日 HH時mm分".to_string(),
         ..AppConfig::default()
@@ -230,7 +231,7 @@ fn test_config_edge_cases() {
     // Test with empty strings
     let empty_config = AppConfig {
         instance_url: "".to_string(),
-        topics: "".to_string(),
+        topics: parse_topics(""),
         poll_rate: 0,
         datetime_format: "".to_string(),
         ..AppConfig::default()
@@ -23,6 +23,11 @@ fn test_notification_data_structure() {
         sound: NotificationSound::Alert,
         persistent: true,
         icon_url: Some("https://example.com/icon.png".to_string()),
+        icon_path: None,
+        tag: None,
+        click: None,
+        server: None,
+        actions: Vec::new(),
     };
 
     assert_eq!(notification.title, "Test Title");
@@ -51,6 +56,11 @@ fn test_notification_data_without_subtitle() {
         sound: NotificationSound::Default,
         persistent: false,
         icon_url: None,
+        icon_path: None,
+        tag: None,
+        click: None,
+        server: None,
+        actions: Vec::new(),
     };
 
     assert_eq!(notification.title, "Title Only");
@@ -86,6 +96,11 @@ fn test_notification_sound_variants() {
             sound: sound.clone(),
             persistent: false,
             icon_url: None,
+            icon_path: None,
+            tag: None,
+            click: None,
+            server: None,
+            actions: Vec::new(),
         };
 
         assert_eq!(notification.sound, sound);
@@ -153,6 +168,11 @@ fn test_notification_data_equality() {
         sound: NotificationSound::Bell,
         persistent: true,
         icon_url: Some("icon.png".to_string()),
+        icon_path: None,
+        tag: None,
+        click: None,
+        server: None,
+        actions: Vec::new(),
     };
 
     let notification2 = NotificationData {
@@ -165,6 +185,11 @@ fn test_notification_data_equality() {
         sound: NotificationSound::Bell,
         persistent: true,
         icon_url: Some("icon.png".to_string()),
+        icon_path: None,
+        tag: None,
+        click: None,
+        server: None,
+        actions: Vec::new(),
     };
 
     // Same data should be equal
@@ -191,6 +216,11 @@ fn test_notification_data_differences() {
         sound: NotificationSound::Default,
         persistent: false,
         icon_url: None,
+        icon_path: None,
+        tag: None,
+        click: None,
+        server: None,
+        actions: Vec::new(),
     };
 
     // Different title
@@ -249,6 +279,11 @@ fn test_notification_data_clone() {
         sound: NotificationSound::Chime,
         persistent: true,
         icon_url: Some("icon.png".to_string()),
+        icon_path: None,
+        tag: None,
+        click: None,
+        server: None,
+        actions: Vec::new(),
     };
 
     let cloned = original.clone();
@@ -276,6 +311,11 @@ fn test_notification_debug_format() {
         sound: NotificationSound::Alert,
         persistent: true,
         icon_url: Some("icon.png".to_string()),
+        icon_path: None,
+        tag: None,
+        click: None,
+        server: None,
+        actions: Vec::new(),
     };
 
     // Just ensure it doesn't panic
@@ -296,3 +336,21 @@ fn test_persistent_mode_debug() {
     let debug_output = format!("{:?}", mode);
     assert_eq!(debug_output, "UrgentOnly");
 }
+
+#[test]
+fn test_activations_reach_subscribers() {
+    let mut activations = ntfy_desktop::notifications::subscribe_activations();
+    ntfy_desktop::notifications::notify_activated(
+        NotificationData {
+            topic: "deploys".to_string(),
+            click: Some("https://ci.example.com/build/42".to_string()),
+            ..Default::default()
+        },
+        None,
+    );
+
+    let activation = activations.try_recv().unwrap();
+    assert_eq!(activation.data.topic, "deploys");
+    assert_eq!(activation.data.click.as_deref(), Some("https://ci.example.com/build/42"));
+    assert_eq!(activation.action, None);
+}
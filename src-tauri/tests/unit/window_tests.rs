@@ -1,4 +1,5 @@
 use ntfy_desktop::config::AppConfig;
+use ntfy_desktop::subscriptions::parse_topics;
 
 #[test]
 fn test_window_settings_validation() {
@@ -131,7 +132,7 @@ fn test_window_settings_edge_cases() {
     // Test with minimum valid values
     let min_config = AppConfig {
         instance_url: "".to_string(),
-        topics: "".to_string(),
+        topics: parse_topics(""),
         poll_rate: 5, // Minimum poll rate
         datetime_format: "".to_string(),
         start_hidden: false,